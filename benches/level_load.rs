@@ -0,0 +1,33 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use dungeon_game::serialization::*;
+
+fn level_load(c: &mut Criterion) {
+    let legacy = std::fs::read_to_string(level_path("gigachad")).unwrap();
+    let level = Level::from_data(&Level::parse(&legacy, "gigachad").unwrap().into_data());
+    let json = serde_json::to_string(&level).unwrap();
+    let bytes = level.to_bytes().unwrap();
+
+    let mut group = c.benchmark_group("gigachad");
+    group.bench_function("legacy_json", |b| {
+        b.iter(|| {
+            Level::parse(black_box(&legacy), "gigachad")
+                .unwrap()
+                .into_data()
+        })
+    });
+    group.bench_function("json", |b| {
+        b.iter(|| {
+            Level::parse(black_box(&json), "gigachad")
+                .unwrap()
+                .into_data()
+        })
+    });
+    group.bench_function("msgpack", |b| {
+        b.iter(|| Level::from_bytes(black_box(&bytes)).unwrap().into_data())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, level_load);
+criterion_main!(benches);
@@ -1,2 +1,4 @@
 pub mod editor_camera;
+pub mod history;
+pub mod paint_tool;
 pub mod tilemap_editor;
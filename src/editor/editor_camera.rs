@@ -1,3 +1,4 @@
+use crate::input::*;
 use macroquad::prelude::*;
 
 pub struct EditorCamera {
@@ -5,6 +6,12 @@ pub struct EditorCamera {
     speed: f32,
 }
 
+impl Default for EditorCamera {
+    fn default() -> Self {
+        EditorCamera::new()
+    }
+}
+
 impl EditorCamera {
     pub fn new() -> EditorCamera {
         let camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, 600.0, 600.0));
@@ -16,21 +23,11 @@ impl EditorCamera {
         self.camera
     }
 
-    pub fn update_camera(&mut self) {
-        let mut move_dir = Vec2::ZERO;
-
-        if is_key_down(KeyCode::Right) {
-            move_dir.x += 1.0;
-        }
-        if is_key_down(KeyCode::Left) {
-            move_dir.x -= 1.0;
-        }
-        if is_key_down(KeyCode::Down) {
-            move_dir.y += 1.0;
-        }
-        if is_key_down(KeyCode::Up) {
-            move_dir.y -= 1.0;
-        }
+    pub fn update_camera(&mut self, input_map: &InputMap) {
+        let move_dir = vec2(
+            input_map.axis(Action::CameraLeft, Action::CameraRight),
+            input_map.axis(Action::CameraUp, Action::CameraDown),
+        );
 
         self.camera.target += move_dir.normalize_or_zero() * self.speed * 50.0 * get_frame_time();
         self.camera.target = self.camera.target.round();
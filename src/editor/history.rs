@@ -0,0 +1,221 @@
+use crate::enemy::patrol::*;
+use crate::enemy::spawn::*;
+use crate::tile::*;
+
+pub const MAX_HISTORY: usize = 100;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TileChange {
+    col: i32,
+    row: i32,
+    before: TileCell,
+    after: TileCell,
+}
+
+impl TileChange {
+    pub fn new(col: i32, row: i32, before: TileCell, after: TileCell) -> TileChange {
+        TileChange {
+            col,
+            row,
+            before,
+            after,
+        }
+    }
+
+    pub fn cell(&self) -> (i32, i32) {
+        (self.col, self.row)
+    }
+
+    pub fn before(&self) -> TileCell {
+        self.before
+    }
+
+    pub fn after(&self) -> TileCell {
+        self.after
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapSnapshot {
+    tiles: TileMap,
+    patrol_routes: Vec<PatrolRoute>,
+    enemy_spawns: Vec<EnemySpawn>,
+}
+
+impl MapSnapshot {
+    pub fn new(
+        tiles: &TileMap,
+        patrol_routes: &[PatrolRoute],
+        enemy_spawns: &[EnemySpawn],
+    ) -> MapSnapshot {
+        MapSnapshot {
+            tiles: tiles.clone(),
+            patrol_routes: patrol_routes.to_vec(),
+            enemy_spawns: enemy_spawns.to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum EditCommand {
+    Tiles(Vec<TileChange>),
+    EnemySpawns {
+        before: Vec<EnemySpawn>,
+        after: Vec<EnemySpawn>,
+    },
+    PatrolRoutes {
+        before: Vec<PatrolRoute>,
+        after: Vec<PatrolRoute>,
+    },
+    Resize {
+        before: Box<MapSnapshot>,
+        after: Box<MapSnapshot>,
+    },
+}
+
+impl EditCommand {
+    fn apply(
+        &self,
+        undo: bool,
+        tiles: &mut TileMap,
+        patrol_routes: &mut Vec<PatrolRoute>,
+        enemy_spawns: &mut Vec<EnemySpawn>,
+    ) {
+        match self {
+            EditCommand::Tiles(changes) => {
+                for change in changes.iter() {
+                    let cell = if undo { change.before } else { change.after };
+                    tiles.set_cell(change.col, change.row, cell);
+                }
+            }
+            EditCommand::EnemySpawns { before, after } => {
+                *enemy_spawns = if undo { before.clone() } else { after.clone() };
+            }
+            EditCommand::PatrolRoutes { before, after } => {
+                *patrol_routes = if undo { before.clone() } else { after.clone() };
+            }
+            EditCommand::Resize { before, after } => {
+                let snapshot = if undo { before } else { after };
+                *tiles = snapshot.tiles.clone();
+                *patrol_routes = snapshot.patrol_routes.clone();
+                *enemy_spawns = snapshot.enemy_spawns.clone();
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<EditCommand>,
+    redo: Vec<EditCommand>,
+    stroke: Vec<TileChange>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || !self.stroke.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn in_stroke(&self) -> bool {
+        !self.stroke.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.stroke.clear();
+    }
+
+    pub fn push(&mut self, command: EditCommand) {
+        self.end_stroke();
+        self.push_command(command);
+    }
+
+    fn push_command(&mut self, command: EditCommand) {
+        self.redo.clear();
+        self.undo.push(command);
+
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn record_cell(&mut self, col: i32, row: i32, before: TileCell, after: TileCell) {
+        if before == after {
+            return;
+        }
+
+        match self
+            .stroke
+            .iter_mut()
+            .find(|change| change.cell() == (col, row))
+        {
+            Some(change) => change.after = after,
+            None => self.stroke.push(TileChange::new(col, row, before, after)),
+        }
+    }
+
+    pub fn end_stroke(&mut self) {
+        let stroke: Vec<TileChange> = self
+            .stroke
+            .drain(..)
+            .filter(|change| change.before != change.after)
+            .collect();
+
+        if !stroke.is_empty() {
+            self.push_command(EditCommand::Tiles(stroke));
+        }
+    }
+
+    pub fn undo(
+        &mut self,
+        tiles: &mut TileMap,
+        patrol_routes: &mut Vec<PatrolRoute>,
+        enemy_spawns: &mut Vec<EnemySpawn>,
+    ) -> bool {
+        self.end_stroke();
+
+        match self.undo.pop() {
+            Some(command) => {
+                command.apply(true, tiles, patrol_routes, enemy_spawns);
+                self.redo.push(command);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(
+        &mut self,
+        tiles: &mut TileMap,
+        patrol_routes: &mut Vec<PatrolRoute>,
+        enemy_spawns: &mut Vec<EnemySpawn>,
+    ) -> bool {
+        self.end_stroke();
+
+        match self.redo.pop() {
+            Some(command) => {
+                command.apply(false, tiles, patrol_routes, enemy_spawns);
+                self.undo.push(command);
+                true
+            }
+            None => false,
+        }
+    }
+}
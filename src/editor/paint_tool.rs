@@ -0,0 +1,153 @@
+use crate::tile::*;
+
+use std::collections::VecDeque;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PaintTool {
+    Brush,
+    Rect,
+    HollowRect,
+    Line,
+    Fill,
+}
+
+impl PaintTool {
+    pub const ALL: [PaintTool; 5] = [
+        PaintTool::Brush,
+        PaintTool::Rect,
+        PaintTool::HollowRect,
+        PaintTool::Line,
+        PaintTool::Fill,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaintTool::Brush => "Brush",
+            PaintTool::Rect => "Rect",
+            PaintTool::HollowRect => "Hollow",
+            PaintTool::Line => "Line",
+            PaintTool::Fill => "Fill",
+        }
+    }
+
+    pub fn next(&self) -> PaintTool {
+        let index = PaintTool::ALL
+            .iter()
+            .position(|tool| tool == self)
+            .unwrap_or(0);
+
+        PaintTool::ALL[(index + 1) % PaintTool::ALL.len()]
+    }
+
+    pub fn is_shape(&self) -> bool {
+        matches!(
+            self,
+            PaintTool::Rect | PaintTool::HollowRect | PaintTool::Line
+        )
+    }
+
+    pub fn shape(&self, start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+        match self {
+            PaintTool::Rect => rect_cells(start, end),
+            PaintTool::HollowRect => hollow_rect_cells(start, end),
+            PaintTool::Line => line_cells(start, end),
+            PaintTool::Brush => vec![end],
+            PaintTool::Fill => Vec::new(),
+        }
+    }
+}
+
+pub fn rect_cells(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+    let (min_col, max_col) = (start.0.min(end.0), start.0.max(end.0));
+    let (min_row, max_row) = (start.1.min(end.1), start.1.max(end.1));
+
+    let mut cells = Vec::new();
+    for row in min_row..=max_row {
+        for col in min_col..=max_col {
+            cells.push((col, row));
+        }
+    }
+
+    cells
+}
+
+pub fn hollow_rect_cells(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+    let (min_col, max_col) = (start.0.min(end.0), start.0.max(end.0));
+    let (min_row, max_row) = (start.1.min(end.1), start.1.max(end.1));
+
+    rect_cells(start, end)
+        .into_iter()
+        .filter(|&(col, row)| col == min_col || col == max_col || row == min_row || row == max_row)
+        .collect()
+}
+
+pub fn line_cells(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+    let (mut col, mut row) = start;
+    let dx = (end.0 - start.0).abs();
+    let dy = -(end.1 - start.1).abs();
+    let step_x = if start.0 < end.0 { 1 } else { -1 };
+    let step_y = if start.1 < end.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut cells = vec![(col, row)];
+    while (col, row) != end {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            col += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            row += step_y;
+        }
+        cells.push((col, row));
+    }
+
+    cells
+}
+
+pub fn flood_fill(tiles: &TileMap, layer: Layer, start: (i32, i32)) -> Vec<(i32, i32)> {
+    let target = match tiles.cell(start.0, start.1) {
+        Some(cell) => cell,
+        None => return Vec::new(),
+    };
+
+    let mut visited = vec![false; tiles.len()];
+    let mut region = Vec::new();
+    let mut open = VecDeque::new();
+
+    visited[(start.1 * tiles.width() + start.0) as usize] = true;
+    open.push_back(start);
+
+    while let Some((col, row)) = open.pop_front() {
+        region.push((col, row));
+
+        for (x, y) in [
+            (col + 1, row),
+            (col - 1, row),
+            (col, row + 1),
+            (col, row - 1),
+        ] {
+            let cell = match tiles.cell(x, y) {
+                Some(cell) => cell,
+                None => continue,
+            };
+
+            let index = (y * tiles.width() + x) as usize;
+            if !visited[index] && same_on_layer(&cell, &target, layer) {
+                visited[index] = true;
+                open.push_back((x, y));
+            }
+        }
+    }
+
+    region
+}
+
+fn same_on_layer(a: &TileCell, b: &TileCell, layer: Layer) -> bool {
+    match layer {
+        Layer::Ground => a.tile.source() == b.tile.source(),
+        Layer::Decoration | Layer::Overlay => a.source(layer) == b.source(layer),
+        Layer::Collision => a.solid == b.solid,
+    }
+}
@@ -1,4 +1,12 @@
 use crate::editor::editor_camera::*;
+use crate::editor::history::*;
+use crate::editor::paint_tool::*;
+use crate::enemy::patrol::*;
+use crate::enemy::spawn::*;
+use crate::enemy::*;
+use crate::input::*;
+use crate::serialization::directory::*;
+use crate::serialization::validation::*;
 use crate::serialization::*;
 use crate::tile::*;
 use crate::utility::*;
@@ -7,14 +15,29 @@ use macroquad::{hash, prelude::*, ui, ui::root_ui};
 pub enum EditorMode {
     None,
     Paint,
+    Patrol,
 }
 
 pub struct TileMapEditor {
     sample_x: f32,
     show_edit_window: bool,
-    is_collision_paint: bool,
+    active_layer: Layer,
+    last_visual_layer: Layer,
+    layer_visible: [bool; 4],
+    layer_locked: [bool; 4],
+    tool: PaintTool,
+    drag: Option<((i32, i32), bool)>,
     sample_y: f32,
     filename: String,
+    levels_dir: String,
+    level_entries: Vec<LevelEntry>,
+    current_file: Option<String>,
+    pending_overwrite: Option<String>,
+    atlas: String,
+    status: String,
+    diagnostics: Vec<Diagnostic>,
+    needs_validation: bool,
+    play_blocked: bool,
     h_slice: f32,
     v_slice: f32,
     tile_scale: f32,
@@ -24,7 +47,16 @@ pub struct TileMapEditor {
     tilemap_source: Texture2D,
     pub editor_camera: EditorCamera,
     map_size: (i32, i32),
-    pub tiles: Vec<Tile>,
+    resize_anchor: Anchor,
+    pub tiles: TileMap,
+    pub patrol_routes: Vec<PatrolRoute>,
+    selected_route: usize,
+    pub enemy_spawns: Vec<EnemySpawn>,
+    history: History,
+    saved: Option<MapSnapshot>,
+    pub random_spawn_fallback: bool,
+    spawn_kind: EnemyKind,
+    spawn_count_slider: f32,
     padding: (f32, f32),
     can_paint: bool,
     editor_mode: EditorMode,
@@ -43,17 +75,40 @@ impl TileMapEditor {
             spawn_set: false,
             sample_x,
             sample_y,
-            is_collision_paint: false,
+            active_layer: Layer::Ground,
+            last_visual_layer: Layer::Ground,
+            layer_visible: [true; 4],
+            layer_locked: [false; 4],
+            tool: PaintTool::Brush,
+            drag: None,
             show_edit_window: true,
             filename: String::new(),
+            levels_dir: LEVEL_DIR.to_string(),
+            level_entries: list_levels(LEVEL_DIR).unwrap_or_default(),
+            current_file: None,
+            pending_overwrite: None,
+            atlas: DEFAULT_ATLAS.to_string(),
+            status: String::new(),
+            diagnostics: Vec::new(),
+            needs_validation: false,
+            play_blocked: false,
             map_width_slider: 10.0,
             map_height_slider: 10.0,
             h_slice,
             editor_camera: EditorCamera::new(),
             v_slice,
             map_size,
+            resize_anchor: Anchor::TopLeft,
             tilemap_source: Texture2D::empty(),
-            tiles: vec![],
+            tiles: TileMap::new(0, 0),
+            patrol_routes: Vec::new(),
+            selected_route: 0,
+            enemy_spawns: Vec::new(),
+            history: History::new(),
+            saved: None,
+            random_spawn_fallback: true,
+            spawn_kind: EnemyKind::Sword,
+            spawn_count_slider: 1.0,
             tile_scale,
             padding,
             editor_mode: EditorMode::None,
@@ -66,17 +121,20 @@ impl TileMapEditor {
             .camera()
             .screen_to_world(Vec2::new(mouse_position().0, mouse_position().1));
 
-        let result_x = (mouse_pos.x as i32 / 32) * 32;
-        let result_y = (mouse_pos.y as i32 / 32) * 32;
+        let (col, row) = world_to_grid(vec2(
+            mouse_pos.x / self.padding.0,
+            mouse_pos.y / self.padding.1,
+        ));
+        grid_to_world(col, row)
+    }
 
-        Vec2::new(
-            result_x as f32 * self.padding.0,
-            result_y as f32 * self.padding.1,
-        )
+    fn padded(&self, pos: Vec2) -> Vec2 {
+        vec2(pos.x * self.padding.0, pos.y * self.padding.1)
     }
 
     pub fn set_texture(&mut self, texture: Texture2D) {
         self.tilemap_source = texture;
+        self.needs_validation = true;
     }
 
     pub fn current_rect(&self) -> Rect {
@@ -92,86 +150,531 @@ impl TileMapEditor {
         self.can_paint
     }
 
-    pub fn edit_tiles(&mut self) {
-        if is_mouse_button_down(MouseButton::Left) {
-            let pos = self.mouse_to_grid();
+    pub fn active_layer(&self) -> Layer {
+        self.active_layer
+    }
 
-            let grid = self.current_rect();
-            for tile in self.tiles.iter_mut() {
-                if tile.pos() == pos {
-                    let tile_type = if !self.is_collision_paint {
-                        tile.set_source(grid);
-                        TileType::Floor(1)
-                    } else {
-                        TileType::Wall(2)
-                    };
+    pub fn set_active_layer(&mut self, layer: Layer) {
+        if layer.is_visual() {
+            self.last_visual_layer = layer;
+        }
+        self.active_layer = layer;
+    }
+
+    pub fn layer_visible(&self, layer: Layer) -> bool {
+        self.layer_visible[layer.index()]
+    }
+
+    pub fn layer_locked(&self, layer: Layer) -> bool {
+        self.layer_locked[layer.index()]
+    }
+
+    pub fn draw_layers(&self, tilemap: Texture2D, debug_collider: bool) {
+        for layer in Layer::ALL.iter() {
+            if !self.layer_visible(*layer) {
+                continue;
+            }
+
+            if layer.is_visual() || debug_collider || self.active_layer == Layer::Collision {
+                draw_padded_layer(&self.tiles, tilemap, *layer, self.padding);
+            }
+        }
+    }
 
-                    tile.set_type(tile_type);
+    pub fn edit_tiles(&mut self, input_map: &InputMap) {
+        if input_map.pressed(Action::ToggleCollisionPaint) {
+            let layer = if self.active_layer == Layer::Collision {
+                self.last_visual_layer
+            } else {
+                Layer::Collision
+            };
+            self.set_active_layer(layer);
+        }
+
+        if input_map.pressed(Action::NextTool) {
+            self.set_tool(self.tool.next());
+        }
+
+        let layer = self.active_layer;
+        if self.layer_locked(layer) {
+            return;
+        }
+
+        match self.tool {
+            PaintTool::Brush => {
+                if input_map.down(Action::Paint) {
+                    let pos = self.mouse_to_grid();
+
+                    let grid = self.current_rect();
+                    self.edit_cell(pos, |cell| paint_cell(cell, layer, grid));
+                }
+
+                if input_map.down(Action::Erase) {
+                    let pos = self.mouse_to_grid();
+
+                    self.edit_cell(pos, |cell| erase_cell(cell, layer));
+                }
+            }
+            PaintTool::Fill => {
+                let erase = input_map.pressed(Action::Erase);
+                if (input_map.pressed(Action::Paint) || erase) && !self.mouse_over_ui() {
+                    let cells = flood_fill(&self.tiles, layer, self.cursor_cell());
+                    self.apply_cells(&cells, layer, erase);
                 }
             }
+            tool if tool.is_shape() => self.drag_shape(input_map, layer),
+            _ => {}
+        }
+
+        if input_map.pressed(Action::PlacePlayerSpawn) {
+            self.place_player_spawn(self.mouse_to_grid());
         }
 
-        if is_key_pressed(KeyCode::C) {
-            self.is_collision_paint = !self.is_collision_paint;
+        if input_map.pressed(Action::RemovePlayerSpawn) {
+            self.remove_player_spawn();
         }
 
-        if is_mouse_button_down(MouseButton::Right) {
+        let spawns_before = self.enemy_spawns.clone();
+
+        if input_map.pressed(Action::PlaceEnemySpawn) {
             let pos = self.mouse_to_grid();
 
-            for tile in self.tiles.iter_mut() {
-                if tile.pos() == pos {
-                    let tile_type = if !self.is_collision_paint {
-                        TileType::Empty(0)
-                    } else {
-                        TileType::Floor(1)
-                    };
+            if self.tiles.tile_at(pos).is_some() {
+                self.enemy_spawns.retain(|spawn| spawn.pos() != pos);
+                self.enemy_spawns.push(EnemySpawn::new(
+                    pos,
+                    self.spawn_kind,
+                    self.spawn_count_slider as u32,
+                ));
+            }
+        }
+
+        if input_map.pressed(Action::RemoveEnemySpawn) {
+            let pos = self.mouse_to_grid();
+            self.enemy_spawns.retain(|spawn| spawn.pos() != pos);
+        }
+
+        if self.enemy_spawns != spawns_before {
+            self.history.push(EditCommand::EnemySpawns {
+                before: spawns_before,
+                after: self.enemy_spawns.clone(),
+            });
+        }
+    }
+
+    pub fn tool(&self) -> PaintTool {
+        self.tool
+    }
+
+    pub fn set_tool(&mut self, tool: PaintTool) {
+        self.tool = tool;
+        self.drag = None;
+    }
+
+    fn cursor_cell(&self) -> (i32, i32) {
+        self.tiles.world_to_grid(self.mouse_to_grid())
+    }
+
+    fn drag_shape(&mut self, input_map: &InputMap, layer: Layer) {
+        let cursor = self.cursor_cell();
+
+        match self.drag {
+            None => {
+                if self.mouse_over_ui() {
+                    return;
+                }
 
-                    tile.set_type(tile_type);
+                if input_map.pressed(Action::Paint) {
+                    self.drag = Some((cursor, false));
+                } else if input_map.pressed(Action::Erase) {
+                    self.drag = Some((cursor, true));
+                }
+            }
+            Some((start, erase)) => {
+                let held = if erase {
+                    input_map.down(Action::Erase)
+                } else {
+                    input_map.down(Action::Paint)
+                };
+
+                if !held {
+                    let cells = self.tool.shape(start, cursor);
+                    self.apply_cells(&cells, layer, erase);
+                    self.drag = None;
                 }
             }
         }
+    }
 
-        if is_key_pressed(KeyCode::P) && is_key_down(KeyCode::LeftControl) && !self.spawn_set {
-            let pos = self.mouse_to_grid();
+    fn apply_cells(&mut self, cells: &[(i32, i32)], layer: Layer, erase: bool) {
+        let source = self.current_rect();
 
-            for tile in self.tiles.iter_mut() {
-                if tile.pos() == pos {
-                    self.spawn_set = true;
-                    tile.set_type(TileType::PlayerSpawn(3));
+        self.history.end_stroke();
+        for &(col, row) in cells.iter() {
+            self.edit_cell(grid_to_world(col, row), |cell| {
+                if erase {
+                    erase_cell(cell, layer)
+                } else {
+                    paint_cell(cell, layer, source)
                 }
+            });
+        }
+        self.history.end_stroke();
+    }
+
+    pub fn tool_preview(&self) -> Vec<(i32, i32)> {
+        let cursor = self.cursor_cell();
+
+        let cells = match (self.tool, self.drag) {
+            (_, Some((start, _))) => self.tool.shape(start, cursor),
+            (PaintTool::Fill, None) => flood_fill(&self.tiles, self.active_layer, cursor),
+            _ => vec![cursor],
+        };
+
+        cells
+            .into_iter()
+            .filter(|&(col, row)| self.tiles.in_bounds(col, row))
+            .collect()
+    }
+
+    pub fn draw_tool_preview(&self) {
+        if !matches!(self.editor_mode, EditorMode::Paint)
+            || !self.can_edit()
+            || self.layer_locked(self.active_layer)
+            || (self.drag.is_none() && self.mouse_over_ui())
+        {
+            return;
+        }
+
+        let erase = matches!(self.drag, Some((_, true)));
+        let source = self.current_rect();
+
+        for (col, row) in self.tool_preview() {
+            let pos = self.padded(grid_to_world(col, row));
+
+            if !erase && self.active_layer.is_visual() {
+                draw_texture_ex(
+                    self.tilemap_source,
+                    pos.x,
+                    pos.y,
+                    Color::new(1.0, 1.0, 1.0, 0.6),
+                    DrawTextureParams {
+                        dest_size: Some(vec2(32.0, 32.0)),
+                        source: Some(source),
+                        ..Default::default()
+                    },
+                );
+            }
+
+            let color = if erase {
+                Color::new(1.0, 0.0, 0.0, 0.3)
+            } else if self.active_layer.is_visual() {
+                Color::new(1.0, 1.0, 0.0, 0.2)
+            } else {
+                Color::new(0.0, 0.0, 1.0, 0.3)
+            };
+            draw_rectangle(pos.x, pos.y, 32.0, 32.0, color);
+        }
+    }
+
+    pub fn place_player_spawn(&mut self, pos: Vec2) {
+        if self.spawn_set || self.tiles.tile_at(pos).is_none() {
+            return;
+        }
+
+        self.spawn_set = true;
+        self.history.end_stroke();
+        self.edit_cell(pos, |cell| {
+            cell.tile.set_type(TileType::PlayerSpawn(3));
+            cell.solid = false;
+        });
+        self.history.end_stroke();
+    }
+
+    pub fn remove_player_spawn(&mut self) {
+        if !self.spawn_set {
+            return;
+        }
+
+        let spawns: Vec<Vec2> = self
+            .tiles
+            .cells()
+            .filter(|(_, _, tile)| matches!(tile.tile_type(), TileType::PlayerSpawn(_)))
+            .map(|(col, row, _)| grid_to_world(col, row))
+            .collect();
+
+        self.spawn_set = false;
+        self.history.end_stroke();
+        for pos in spawns {
+            self.edit_tile(pos, |tile| tile.set_type(ground_type(tile.source())));
+        }
+        self.history.end_stroke();
+    }
+
+    fn edit_tile<F: FnOnce(&mut Tile)>(&mut self, pos: Vec2, edit: F) {
+        self.edit_cell(pos, |cell| edit(&mut cell.tile));
+    }
+
+    fn edit_cell<F: FnOnce(&mut TileCell)>(&mut self, pos: Vec2, edit: F) {
+        let (col, row) = self.tiles.world_to_grid(pos);
+
+        if let Some(before) = self.tiles.cell(col, row) {
+            let mut after = before;
+            edit(&mut after);
+            self.tiles.set_cell(col, row, after);
+            self.history.record_cell(col, row, before, after);
+            self.needs_validation |= before != after;
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if self.history.undo(
+            &mut self.tiles,
+            &mut self.patrol_routes,
+            &mut self.enemy_spawns,
+        ) {
+            self.sync_with_tiles();
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if self.history.redo(
+            &mut self.tiles,
+            &mut self.patrol_routes,
+            &mut self.enemy_spawns,
+        ) {
+            self.sync_with_tiles();
+        }
+    }
+
+    fn sync_with_tiles(&mut self) {
+        self.spawn_set = self
+            .tiles
+            .iter()
+            .any(|tile| matches!(tile.tile_type(), TileType::PlayerSpawn(_)));
+        self.map_size = (self.tiles.width(), self.tiles.height());
+        self.map_width_slider = self.map_size.0 as f32;
+        self.map_height_slider = self.map_size.1 as f32;
+        self.selected_route = self
+            .selected_route
+            .min(self.patrol_routes.len().saturating_sub(1));
+        self.needs_validation = true;
+    }
+
+    fn level_data(&self, name: &str) -> LevelData {
+        LevelData {
+            name: name.to_string(),
+            atlas: self.atlas.clone(),
+            tiles: self.tiles.clone(),
+            patrol_routes: self.patrol_routes.clone(),
+            enemy_spawns: self.enemy_spawns.clone(),
+        }
+    }
+
+    fn resize_map(&mut self) {
+        let (width, height) = (self.map_width_slider as i32, self.map_height_slider as i32);
+        let (level, spawn_move) =
+            resize_level(&self.level_data(""), width, height, self.resize_anchor);
+
+        self.replace_map(level);
+        self.status = match spawn_move {
+            SpawnMove::Moved(col, row) => format!("Player spawn moved to ({}, {})", col, row),
+            SpawnMove::NoFloor => {
+                "Player spawn was cropped and no floor is left for it".to_string()
             }
+            SpawnMove::Unchanged => format!("Resized to {}x{}", width, height),
+        };
+    }
+
+    fn replace_map(&mut self, level: LevelData) {
+        let before = MapSnapshot::new(&self.tiles, &self.patrol_routes, &self.enemy_spawns);
+
+        self.tiles = level.tiles;
+        self.patrol_routes = level.patrol_routes;
+        self.enemy_spawns = level.enemy_spawns;
+        self.selected_route = 0;
+        self.sync_with_tiles();
+
+        let after = MapSnapshot::new(&self.tiles, &self.patrol_routes, &self.enemy_spawns);
+        self.history.push(EditCommand::Resize {
+            before: Box::new(before),
+            after: Box::new(after),
+        });
+    }
+
+    pub fn draw_enemy_spawns(&self) {
+        for spawn in self.enemy_spawns.iter() {
+            let pos = self.padded(spawn.pos());
+
+            draw_rectangle_lines(pos.x + 2.0, pos.y + 2.0, 28.0, 28.0, 2.0, RED);
+            draw_text(
+                &format!("{}x{}", spawn.count(), spawn.kind().name()),
+                pos.x + 2.0,
+                pos.y + 20.0,
+                12.0,
+                RED,
+            );
+        }
+    }
+
+    fn mouse_over_ui(&self) -> bool {
+        root_ui().is_mouse_over(Vec2::new(mouse_position().0, mouse_position().1))
+    }
+
+    fn record_routes(&mut self, before: Vec<PatrolRoute>) {
+        if self.patrol_routes != before {
+            self.history.push(EditCommand::PatrolRoutes {
+                before,
+                after: self.patrol_routes.clone(),
+            });
         }
+    }
+
+    pub fn edit_patrol_routes(&mut self, input_map: &InputMap) {
+        let before = self.patrol_routes.clone();
+        self.change_patrol_routes(input_map);
+        self.record_routes(before);
+    }
 
-        if is_key_pressed(KeyCode::R) && is_key_down(KeyCode::LeftControl) && self.spawn_set {
-            for tile in self.tiles.iter_mut() {
-                if let TileType::PlayerSpawn(_) = tile.tile_type() {
-                    self.spawn_set = false;
-                    tile.set_type(TileType::Floor(1));
+    fn change_patrol_routes(&mut self, input_map: &InputMap) {
+        if input_map.pressed(Action::NewPatrolRoute) {
+            self.patrol_routes.push(PatrolRoute::default());
+            self.selected_route = self.patrol_routes.len() - 1;
+        }
+
+        if self.mouse_over_ui() {
+            return;
+        }
+
+        if input_map.pressed(Action::AddWaypoint) {
+            let pos = self.mouse_to_grid();
+
+            if self.tiles.tile_at(pos).is_some() {
+                if self.patrol_routes.is_empty() {
+                    self.patrol_routes.push(PatrolRoute::default());
+                    self.selected_route = 0;
                 }
+
+                if let Some(route) = self.patrol_routes.get_mut(self.selected_route) {
+                    route.push(pos);
+                }
+            }
+        }
+
+        if input_map.pressed(Action::RemoveWaypoint) {
+            if let Some(route) = self.patrol_routes.get_mut(self.selected_route) {
+                route.pop();
+            }
+        }
+    }
+
+    pub fn draw_patrol_routes(&self) {
+        for (index, route) in self.patrol_routes.iter().enumerate() {
+            let color = if index == self.selected_route {
+                YELLOW
+            } else {
+                ORANGE
+            };
+
+            let waypoints = route.waypoints();
+            for (waypoint_index, waypoint) in waypoints.iter().enumerate() {
+                let center = self.padded(*waypoint) + vec2(16.0, 16.0);
+                let next = self.padded(waypoints[(waypoint_index + 1) % waypoints.len()])
+                    + vec2(16.0, 16.0);
+
+                draw_line(center.x, center.y, next.x, next.y, 2.0, color);
+                draw_circle(center.x, center.y, 5.0, color);
+            }
+
+            if let Some(first) = waypoints.first().map(|waypoint| self.padded(*waypoint)) {
+                draw_text(
+                    &format!("{}", index + 1),
+                    first.x + 2.0,
+                    first.y + 12.0,
+                    16.0,
+                    color,
+                );
             }
         }
     }
 
-    pub fn show_editors(&mut self) {
+    pub fn patrol_editor(&mut self) {
+        let before = self.patrol_routes.clone();
+
+        ui::widgets::Window::new(hash!(), vec2(500.0, 0.0), vec2(200.0, 150.0))
+            .label("Patrol Routes")
+            .titlebar(true)
+            .ui(&mut root_ui(), |ui| {
+                let count = self.patrol_routes.len();
+                let waypoints = self
+                    .patrol_routes
+                    .get(self.selected_route)
+                    .map(|route| route.len())
+                    .unwrap_or(0);
+
+                if count == 0 {
+                    ui.label(None, "No routes");
+                } else {
+                    ui.label(
+                        None,
+                        &format!(
+                            "Route {}/{} ({} waypoints)",
+                            self.selected_route + 1,
+                            count,
+                            waypoints
+                        ),
+                    );
+                }
+
+                if ui.button(None, "New Route") {
+                    self.patrol_routes.push(PatrolRoute::default());
+                    self.selected_route = self.patrol_routes.len() - 1;
+                }
+                if ui.button(None, "Previous") && count > 0 {
+                    self.selected_route = (self.selected_route + count - 1) % count;
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Next") && count > 0 {
+                    self.selected_route = (self.selected_route + 1) % count;
+                }
+                if ui.button(None, "Delete Route") && self.selected_route < count {
+                    self.patrol_routes.remove(self.selected_route);
+                    self.selected_route = self.selected_route.saturating_sub(1);
+                }
+            });
+
+        self.record_routes(before);
+    }
+
+    pub fn show_editors(&mut self, input_map: &InputMap) {
+        if !input_map.down(Action::Paint) && !input_map.down(Action::Erase) {
+            self.history.end_stroke();
+        }
+
+        if input_map.pressed(Action::Undo) {
+            self.undo();
+        }
+        if input_map.pressed(Action::Redo) {
+            self.redo();
+        }
+
         match self.editor_mode {
             EditorMode::None => {
                 self.show_edit_window = true;
-                self.is_collision_paint = false;
                 self.can_paint = false;
+                self.drag = None;
             }
             EditorMode::Paint => {
                 if self.can_edit() {
-                    self.edit_tiles();
+                    self.edit_tiles(input_map);
                 }
 
-                if is_key_pressed(KeyCode::E) && is_key_down(KeyCode::LeftControl) {
+                if input_map.pressed(Action::TogglePaint) {
                     self.can_paint = !self.can_paint;
-                    if self.can_paint == false {
-                        self.is_collision_paint = false;
-                    }
+                    self.drag = None;
                 }
 
-                if is_key_pressed(KeyCode::Q) && is_key_down(KeyCode::LeftControl) {
+                if input_map.pressed(Action::ToggleEditWindow) {
                     self.show_edit_window = !self.show_edit_window;
                 }
 
@@ -182,24 +685,102 @@ impl TileMapEditor {
 
                 if self.can_edit() {
                     draw_text("Edit Mode", 0.0, 20.0, 16.0, RED);
-                    if self.is_collision_paint {
-                        draw_text("Collision Paint", 0.0, 40.0, 16.0, YELLOW);
-                    }
+                    let color = if self.layer_locked(self.active_layer) {
+                        GRAY
+                    } else {
+                        YELLOW
+                    };
+                    draw_text(
+                        &format!("{} Layer - {}", self.active_layer.name(), self.tool.name()),
+                        0.0,
+                        40.0,
+                        16.0,
+                        color,
+                    );
                 }
 
-                if is_key_pressed(KeyCode::F3) {
-                    self.is_collision_paint = false;
+                if input_map.pressed(Action::StopPainting) {
                     self.can_paint = false;
+                    self.drag = None;
                 }
             }
+            EditorMode::Patrol => {
+                self.can_paint = false;
+
+                self.edit_patrol_routes(input_map);
+                self.patrol_editor();
+
+                draw_text("Patrol Mode", 0.0, 20.0, 16.0, ORANGE);
+            }
+        }
+
+        if self.needs_validation {
+            self.validate();
+        }
+        if !self.diagnostics.is_empty() {
+            self.validation_window();
+        }
+    }
+
+    pub fn validate(&mut self) -> bool {
+        let atlas_size = vec2(self.tilemap_source.width(), self.tilemap_source.height());
+        self.diagnostics = validate_level(&self.tiles, atlas_size);
+        self.needs_validation = false;
+
+        let valid = !has_errors(&self.diagnostics);
+        if valid {
+            self.play_blocked = false;
         }
+
+        valid
+    }
+
+    pub fn ready_to_play(&mut self) -> bool {
+        let valid = self.validate();
+        self.play_blocked = !valid;
+
+        valid
+    }
+
+    pub fn needs_validation(&self) -> bool {
+        self.needs_validation
+    }
+
+    pub fn play_blocked(&self) -> bool {
+        self.play_blocked
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn validation_window(&self) {
+        ui::widgets::Window::new(
+            hash!(),
+            vec2(0.0, screen_height() - 230.0),
+            vec2(400.0, 200.0),
+        )
+        .label("Validation")
+        .titlebar(true)
+        .ui(&mut root_ui(), |ui| {
+            if self.play_blocked {
+                ui.label(None, "Can't play: fix the errors below first.");
+            }
+            for diagnostic in self.diagnostics.iter() {
+                let prefix = match diagnostic.severity() {
+                    Severity::Error => "Error",
+                    Severity::Warning => "Warning",
+                };
+                ui.label(None, &format!("{}: {}", prefix, diagnostic.message()));
+            }
+        });
     }
 
     pub fn draw(&mut self) {
         ui::widgets::Window::new(ui::hash!(), vec2(0.0, 0.0), vec2(300.0, 300.0))
             .label("Editor")
             .titlebar(true)
-            .ui(&mut *ui::root_ui(), |ui| {
+            .ui(&mut ui::root_ui(), |ui| {
                 ui.tree_node(ui::hash!(), "Spritesheet", |ui| {
                     ui.texture(
                         self.tilemap_source,
@@ -230,6 +811,48 @@ impl TileMapEditor {
                     self.sample_y = self.sample_y.round();
                 });
 
+                ui.tree_node(hash!(), "Tools", |ui| {
+                    for (index, tool) in PaintTool::ALL.iter().enumerate() {
+                        if index > 0 {
+                            ui.same_line(0.0);
+                        }
+
+                        let label = if *tool == self.tool {
+                            format!("[{}]", tool.name())
+                        } else {
+                            tool.name().to_string()
+                        };
+                        if ui.button(None, label.as_str()) {
+                            self.set_tool(*tool);
+                        }
+                    }
+                });
+
+                ui.tree_node(hash!(), "Layers", |ui| {
+                    for layer in Layer::ALL.iter() {
+                        let index = layer.index();
+                        let label = if *layer == self.active_layer {
+                            format!("[{}]", layer.name())
+                        } else {
+                            layer.name().to_string()
+                        };
+                        if ui.button(None, label.as_str()) {
+                            self.set_active_layer(*layer);
+                        }
+                        ui.checkbox(
+                            hash!("visible", index),
+                            "Visible",
+                            &mut self.layer_visible[index],
+                        );
+                        ui.same_line(0.0);
+                        ui.checkbox(
+                            hash!("locked", index),
+                            "Locked",
+                            &mut self.layer_locked[index],
+                        );
+                    }
+                });
+
                 ui.tree_node(hash!(), "Map Options", |ui| {
                     ui.slider(hash!(), "Map Width", 1.0..50.0, &mut self.map_width_slider);
                     ui.slider(
@@ -241,17 +864,57 @@ impl TileMapEditor {
                     self.map_width_slider = self.map_width_slider.round();
                     self.map_height_slider = self.map_height_slider.round();
 
-                    if ui.button(Vec2::new(0.0, 290.0), "Update Map (Resets all tiles)") {
-                        self.spawn_set = false;
-                        self.map_size =
-                            (self.map_width_slider as i32, self.map_height_slider as i32);
-                        self.tiles = create_new_map(self.padding, self.map_size.0, self.map_size.1);
+                    ui.label(None, "Anchor");
+                    for row in Anchor::ALL.chunks(3) {
+                        for (index, anchor) in row.iter().enumerate() {
+                            if index > 0 {
+                                ui.same_line(0.0);
+                            }
+
+                            let label = if *anchor == self.resize_anchor {
+                                format!("[{}]", anchor.name())
+                            } else {
+                                format!(" {} ", anchor.name())
+                            };
+                            if ui.button(None, label.as_str()) {
+                                self.resize_anchor = *anchor;
+                            }
+                        }
+                    }
+
+                    if ui.button(None, "Resize Map") {
+                        self.resize_map();
                     }
                 });
 
+                ui.tree_node(hash!(), "Enemy Spawns", |ui| {
+                    ui.label(None, &format!("Kind: {}", self.spawn_kind.name()));
+                    ui.slider(
+                        hash!(),
+                        "Count",
+                        1.0..MAX_SPAWN_COUNT as f32,
+                        &mut self.spawn_count_slider,
+                    );
+                    self.spawn_count_slider = self.spawn_count_slider.round();
+
+                    ui.label(None, &format!("{} placed", self.enemy_spawns.len()));
+                    ui.checkbox(hash!(), "Random Fallback", &mut self.random_spawn_fallback);
+                });
+
                 ui.checkbox(hash!(), "Edit Mode", &mut self.can_paint);
 
-                let selected_texture;
+                if ui.button(None, "Undo") {
+                    self.undo();
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Redo") {
+                    self.redo();
+                }
+                ui.same_line(0.0);
+                ui.label(
+                    None,
+                    &format!("{} / {}", self.history.undo_len(), self.history.redo_len()),
+                );
 
                 let sample_rect = Rect::new(
                     self.sample_x * self.tile_scale,
@@ -260,15 +923,14 @@ impl TileMapEditor {
                     self.tile_scale,
                 );
 
-                if sub_image_valid(&self.tilemap_source(), sample_rect) {
-                    selected_texture = self
-                        .tilemap_source
+                let selected_texture = if sub_image_valid(&self.tilemap_source(), sample_rect) {
+                    self.tilemap_source
                         .get_texture_data()
-                        .sub_image(sample_rect);
+                        .sub_image(sample_rect)
                 } else {
                     ui.label(Vec2::new(0.0, 230.0), "Invalid texture sample.");
-                    selected_texture = Image::empty();
-                }
+                    Image::empty()
+                };
                 ui.texture(
                     Texture2D::from_image(&selected_texture),
                     self.tile_scale,
@@ -281,31 +943,225 @@ impl TileMapEditor {
         self.editor_mode = mode;
     }
 
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    pub fn current_file(&self) -> Option<&str> {
+        self.current_file.as_deref()
+    }
+
     pub fn tilemap_source(&self) -> Texture2D {
         self.tilemap_source
     }
 
+    pub fn levels_dir(&self) -> &str {
+        &self.levels_dir
+    }
+
+    pub fn set_levels_dir(&mut self, dir: &str) {
+        self.levels_dir = dir.to_string();
+        self.current_file = None;
+        self.saved = None;
+        self.pending_overwrite = None;
+        self.refresh_levels();
+    }
+
+    pub fn refresh_levels(&mut self) {
+        match list_levels(&self.levels_dir) {
+            Ok(entries) => self.level_entries = entries,
+            Err(err) => {
+                self.level_entries.clear();
+                self.status = format!("Could not list {}: {}", self.levels_dir, err);
+            }
+        }
+    }
+
     pub fn serialization_editor(&mut self) {
-        ui::widgets::Window::new(hash!(), vec2(100.0, 0.0), vec2(300.0, 300.0))
+        ui::widgets::Window::new(hash!(), vec2(100.0, 0.0), vec2(320.0, 360.0))
             .label("Save/Load")
             .titlebar(true)
-            .ui(&mut *root_ui(), |ui| {
+            .ui(&mut root_ui(), |ui| {
                 ui.input_text(hash!(), "Filename", &mut self.filename);
-                if ui.button(Vec2::new(25.0, 50.0), "Save") && !self.filename.is_empty() {
-                    match save(&self.tiles, &self.filename) {
-                        Ok(_) => eprintln!("{} saved successfully!", self.filename),
-                        Err(_) => eprintln!("{} not saved.", self.filename),
+
+                if ui.button(Vec2::new(5.0, 50.0), "Save") {
+                    match self.current_file.clone() {
+                        Some(path) => self.save_level(&path),
+                        None => self.save_as(),
+                    }
+                }
+                if ui.button(Vec2::new(50.0, 50.0), "Save As") {
+                    self.save_as();
+                }
+                if ui.button(Vec2::new(115.0, 50.0), "Load") && !self.filename.is_empty() {
+                    let path = self.filename.clone();
+                    self.open_level(&path);
+                }
+                if ui.button(Vec2::new(160.0, 50.0), "New") {
+                    self.new_level();
+                }
+                if ui.button(Vec2::new(200.0, 50.0), "Revert") {
+                    match self.current_file.clone() {
+                        Some(path) => self.open_level(&path),
+                        None => self.status = "Nothing to revert".to_string(),
                     }
                 }
-                if ui.button(Vec2::new(70.0, 50.0), "Load") && !self.filename.is_empty() {
-                    self.tiles = load(&self.filename).unwrap();
 
-                    for tile in self.tiles.iter() {
-                        if let TileType::PlayerSpawn(_) = tile.tile_type() {
-                            self.spawn_set = false;
-                        }
+                if let Some(path) = self.pending_overwrite.clone() {
+                    ui.label(Vec2::new(5.0, 75.0), &format!("Overwrite {}?", path));
+                    if ui.button(Vec2::new(200.0, 75.0), "Yes") {
+                        self.pending_overwrite = None;
+                        self.save_level(&path);
+                    }
+                    if ui.button(Vec2::new(240.0, 75.0), "No") {
+                        self.pending_overwrite = None;
+                        self.status = format!("{} not saved", path);
                     }
                 }
+
+                if !self.status.is_empty() {
+                    ui.label(Vec2::new(5.0, 100.0), &self.status);
+                }
+
+                ui.label(Vec2::new(5.0, 125.0), &self.levels_dir);
+                if ui.button(Vec2::new(250.0, 125.0), "Refresh") {
+                    self.refresh_levels();
+                }
+
+                let now = std::time::SystemTime::now();
+                let mut selected = None;
+                ui::widgets::Group::new(hash!(), vec2(310.0, 180.0))
+                    .position(vec2(0.0, 150.0))
+                    .ui(ui, |ui| {
+                        for entry in self.level_entries.iter() {
+                            let marker = if entry.name() == self.filename {
+                                ">"
+                            } else {
+                                " "
+                            };
+                            let label = format!(
+                                "{} {}  {}  {}",
+                                marker,
+                                entry.name(),
+                                entry.size_label(),
+                                entry.modified_label(now)
+                            );
+                            if ui.button(None, label.as_str()) {
+                                selected = Some(entry.name().to_string());
+                            }
+                        }
+                    });
+
+                if let Some(name) = selected {
+                    self.select_entry(&name);
+                }
             });
     }
+
+    pub fn select_entry(&mut self, name: &str) {
+        self.filename = name.to_string();
+        self.pending_overwrite = None;
+    }
+
+    fn save_as(&mut self) {
+        if self.filename.is_empty() {
+            self.status = "Enter a filename first".to_string();
+            return;
+        }
+
+        let path = self.filename.clone();
+        if level_exists(&self.levels_dir, &path) && self.current_file.as_deref() != Some(&path) {
+            self.pending_overwrite = Some(path);
+        } else {
+            self.save_level(&path);
+        }
+    }
+
+    fn save_level(&mut self, path: &str) {
+        let level = self.level_data(level_name(path));
+
+        self.status = match save_to(&self.levels_dir, &level, path) {
+            Ok(_) => {
+                self.current_file = Some(path.to_string());
+                self.filename = path.to_string();
+                self.saved = Some(self.snapshot());
+                self.refresh_levels();
+                format!("{} saved successfully!", path)
+            }
+            Err(err) => format!("{} not saved: {}", path, err),
+        };
+    }
+
+    pub fn open_level(&mut self, path: &str) {
+        match load_from(&self.levels_dir, path) {
+            Ok(level) => {
+                self.load_level(level);
+                self.current_file = Some(path.to_string());
+                self.filename = path.to_string();
+                self.pending_overwrite = None;
+                self.status = format!("{} loaded", path);
+            }
+            Err(err) => self.status = format!("{} not loaded: {}", path, err),
+        }
+    }
+
+    fn new_level(&mut self) {
+        let (width, height) = (self.map_width_slider as i32, self.map_height_slider as i32);
+        self.replace_map(LevelData::new("", TileMap::new(width, height)));
+        self.current_file = None;
+        self.saved = None;
+        self.pending_overwrite = None;
+        self.filename.clear();
+        self.status = format!("New {}x{} level", width, height);
+    }
+
+    fn load_level(&mut self, level: LevelData) {
+        self.history.clear();
+        self.atlas = level.atlas;
+        self.tiles = level.tiles;
+        self.patrol_routes = level.patrol_routes;
+        self.enemy_spawns = level.enemy_spawns;
+        self.selected_route = 0;
+        self.sync_with_tiles();
+        self.saved = Some(self.snapshot());
+    }
+
+    fn snapshot(&self) -> MapSnapshot {
+        MapSnapshot::new(&self.tiles, &self.patrol_routes, &self.enemy_spawns)
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        match &self.saved {
+            Some(saved) => *saved != self.snapshot(),
+            None => true,
+        }
+    }
+}
+
+pub fn paint_cell(cell: &mut TileCell, layer: Layer, source: Rect) {
+    match layer {
+        Layer::Ground => cell.tile.set_source(source),
+        Layer::Decoration | Layer::Overlay => cell.set_source(layer, source),
+        Layer::Collision => {
+            if !matches!(cell.tile.tile_type(), TileType::PlayerSpawn(_)) {
+                cell.solid = true;
+            }
+        }
+    }
+}
+
+pub fn ground_type(source: Rect) -> TileType {
+    if is_empty_source(source) {
+        TileType::Empty(0)
+    } else {
+        TileType::Floor(1)
+    }
+}
+
+pub fn erase_cell(cell: &mut TileCell, layer: Layer) {
+    match layer {
+        Layer::Ground => cell.tile.set_source(empty_source()),
+        Layer::Decoration | Layer::Overlay => cell.set_source(layer, empty_source()),
+        Layer::Collision => cell.solid = false,
+    }
 }
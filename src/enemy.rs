@@ -1,27 +1,81 @@
+pub mod ai;
+pub mod flow_field;
+pub mod navigation;
+pub mod patrol;
+pub mod spawn;
+pub mod vision;
+
+use crate::enemy::ai::*;
+use crate::enemy::flow_field::*;
+use crate::enemy::navigation::*;
+use crate::enemy::patrol::*;
+use crate::enemy::vision::*;
 use crate::player::*;
+use crate::rng::*;
 use crate::tile::*;
+use crate::timestep::*;
 
 use macroquad::prelude::*;
 
 use std::collections::VecDeque;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyKind {
+    Sword,
+}
+
+impl EnemyKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyKind::Sword => "Sword",
+        }
+    }
+
+    pub fn neighbor_mode(&self) -> NeighborMode {
+        match self {
+            EnemyKind::Sword => NeighborMode::Eight { cut_corners: false },
+        }
+    }
+
+    pub fn vision(&self) -> Vision {
+        match self {
+            EnemyKind::Sword => Vision::new(160.0, 120.0_f32.to_radians()),
+        }
+    }
+}
+
+const INVESTIGATE_RADIUS: f32 = 52.0;
+const WAYPOINT_RADIUS: f32 = 4.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum AttackPhase {
+    Strike,
+    Recover,
+}
+
 #[derive(Clone)]
 pub struct Enemy {
-    attack_timer: f32,
+    kind: EnemyKind,
+    ai: StateMachine,
+    attack_phase: AttackPhase,
+    vision: Vision,
+    facing: Vec2,
+    last_seen: Option<Vec2>,
+    patrol: Option<PatrolRoute>,
+    patrol_index: usize,
     invulnerable: bool,
     health: i32,
-    idle: bool,
-    is_attacking: bool,
-    attack_anim_start: bool,
     health_timer: f32,
     speed: f32,
     attack_spot: Vec2,
-    player_spotted: bool,
     move_pos: Vec2,
-    prev_goal: Vec2,
+    prev_goal: Option<Vec2>,
+    following_flow: bool,
     path: VecDeque<Vec2>,
     sword_pos: Vec2,
+    prev_sword_pos: Vec2,
     pos: Vec2,
+    prev_pos: Vec2,
 }
 
 impl Enemy {
@@ -29,337 +83,391 @@ impl Enemy {
         self.health
     }
 
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
     pub fn set_pos(&mut self, pos: Vec2) {
         self.pos = pos;
+        self.prev_pos = pos;
+    }
+
+    pub fn render_pos(&self, alpha: f32) -> Vec2 {
+        self.prev_pos.lerp(self.pos, alpha)
+    }
+
+    pub fn sword_pos(&self) -> Vec2 {
+        self.sword_pos
+    }
+
+    pub fn render_sword_pos(&self, alpha: f32) -> Vec2 {
+        self.prev_sword_pos.lerp(self.sword_pos, alpha)
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        self.invulnerable
+    }
+
+    pub fn weapon_ready(&self) -> bool {
+        self.ai.attack_ready() || self.state() == AiState::Attack
     }
 
     pub fn path(&self) -> VecDeque<Vec2> {
         self.path.clone()
     }
 
-    pub fn player_spotted(&self) -> bool {
-        self.player_spotted
+    pub fn state(&self) -> AiState {
+        self.ai.state()
     }
 
-    pub fn set_player_spotted(&mut self, spotted: bool) {
-        self.player_spotted = spotted
+    pub fn player_spotted(&self) -> bool {
+        matches!(self.ai.state(), AiState::Chase | AiState::Attack)
     }
 
-    pub fn new(pos: Vec2) -> Enemy {
-        Enemy {
-            health_timer: 0.0,
-            health: 3,
-            invulnerable: false,
-            idle: true,
-            attack_timer: 2.0,
-            attack_anim_start: false,
-            is_attacking: false,
-            attack_spot: Vec2::ZERO,
-            sword_pos: Vec2::ZERO,
-            speed: rand::gen_range(90.0, 105.0),
-            player_spotted: false,
-            move_pos: Vec2::ZERO,
-            pos,
-            prev_goal: Vec2::ZERO,
-            path: VecDeque::new(),
-        }
+    pub fn kind(&self) -> EnemyKind {
+        self.kind
     }
 
-    pub fn pos(&self) -> Vec2 {
-        self.pos
+    pub fn vision(&self) -> Vision {
+        self.vision
     }
 
-    pub fn draw(&mut self, texture: Texture2D) {
-        if !self.invulnerable {
-            draw_texture_ex(
-                texture,
-                self.pos.x,
-                self.pos.y,
-                WHITE,
-                DrawTextureParams {
-                    dest_size: Some(vec2(32.0, 32.0)),
-                    ..Default::default()
-                },
-            );
-        } else {
-            self.health_timer += get_frame_time();
-            if self.health_timer > 1.5 {
-                self.health_timer = 0.0;
-                self.invulnerable = false;
-            }
+    pub fn set_vision(&mut self, vision: Vision) {
+        self.vision = vision;
+    }
 
-            draw_texture_ex(
-                texture,
-                self.pos.x,
-                self.pos.y,
-                RED,
-                DrawTextureParams {
-                    dest_size: Some(vec2(32.0, 32.0)),
-                    ..Default::default()
-                },
-            );
-        }
+    pub fn facing(&self) -> Vec2 {
+        self.facing
     }
 
-    pub fn damage_player(&self, player: &mut Player) {
-        if self.sword_pos.distance(player.pos()) < 19.0 && !self.idle {
-            player.take_damage();
+    pub fn set_facing(&mut self, facing: Vec2) {
+        if facing != Vec2::ZERO {
+            self.facing = facing.normalize();
         }
     }
 
-    pub fn take_damage(&mut self) {
-        if !self.invulnerable {
-            self.health -= 1;
-            self.health = self.health.max(0);
+    pub fn last_seen_pos(&self) -> Option<Vec2> {
+        self.last_seen
+    }
 
-            self.idle = true;
-            self.invulnerable = true;
-        }
+    pub fn clear_last_seen(&mut self) {
+        self.last_seen = None;
     }
 
-    pub fn draw_weapon(&mut self, texture: Texture2D, player_pos: Vec2) {
-        if self.player_spotted {
-            let dir = (player_pos - self.pos).normalize_or_zero();
+    fn eye_pos(&self) -> Vec2 {
+        self.pos + vec2(16.0, 16.0)
+    }
 
-            let angle = player_pos.angle_between(self.sword_pos);
+    pub fn can_see(&self, target: Vec2, nav_grid: &NavGrid) -> bool {
+        self.vision.can_see(
+            self.eye_pos(),
+            self.facing,
+            target + vec2(16.0, 16.0),
+            nav_grid,
+        )
+    }
 
-            let flip_y = player_pos.y > self.pos.y;
+    pub fn look_for(&mut self, target: Vec2, nav_grid: &NavGrid) -> bool {
+        let visible = self.can_see(target, nav_grid);
 
-            if self.pos.distance(player_pos) < 50.0 && !self.idle {
-                if !self.is_attacking {
-                    self.is_attacking = true;
-                    self.attack_spot = self.sword_pos + dir * 40.0;
-                }
+        if visible {
+            self.last_seen = Some(target);
+            self.set_facing(target - self.pos);
+        }
 
-                if self.sword_pos.distance(self.attack_spot) > 5.0
-                    && self.attack_anim_start == false
-                    && self.is_attacking
-                {
-                    self.sword_pos = self.sword_pos.lerp(self.attack_spot, 0.06);
-                } else {
-                    self.attack_anim_start = true;
-                    self.sword_pos = self.sword_pos.lerp(self.pos + dir * 20.0, 0.1);
-                    if self.sword_pos.distance(self.pos + dir * 20.0) > 5.0 {
-                        self.attack_anim_start = false;
-                        self.is_attacking = false;
-                        self.idle = true;
-                    }
-                }
+        visible
+    }
 
-                if self.sword_pos.distance(self.pos) > 50.0 {
-                    self.is_attacking = false;
-                    self.attack_anim_start = false;
-                    self.sword_pos = self.pos + dir * 20.0;
-                    self.idle = true;
-                }
+    pub fn investigate(&mut self, tiles: &TileMap, nav_grid: &NavGrid, dt: f32) {
+        if let Some(target) = self.last_seen {
+            if self.pos.distance(target) <= INVESTIGATE_RADIUS {
+                self.last_seen = None;
             } else {
-                if self.idle {
-                    self.attack_timer += get_frame_time();
-                    if self.attack_timer > 2.5 {
-                        self.attack_timer = 0.0;
-                        self.idle = false;
-                    }
-                }
-                self.is_attacking = false;
-                self.sword_pos = self.pos + dir * 20.0;
+                self.move_to(target, tiles, nav_grid, None, dt);
             }
+        }
+    }
 
-            if !self.idle {
-                draw_texture_ex(
-                    texture,
-                    self.sword_pos.x,
-                    self.sword_pos.y,
-                    WHITE,
-                    DrawTextureParams {
-                        dest_size: Some(vec2(32.0, 32.0)),
-                        rotation: angle,
-                        pivot: Some(self.sword_pos),
-                        flip_y,
-                        ..Default::default()
-                    },
-                );
-            } else {
-                draw_texture_ex(
-                    texture,
-                    self.sword_pos.x,
-                    self.sword_pos.y,
-                    RED,
-                    DrawTextureParams {
-                        dest_size: Some(vec2(32.0, 32.0)),
-                        rotation: angle,
-                        pivot: Some(self.sword_pos),
-                        flip_y,
-                        ..Default::default()
-                    },
-                );
+    pub fn patrol(&self) -> Option<&PatrolRoute> {
+        self.patrol.as_ref()
+    }
+
+    pub fn set_patrol(&mut self, route: Option<PatrolRoute>) {
+        self.patrol = route.filter(|route| !route.is_empty());
+        self.patrol_index = 0;
+    }
+
+    pub fn patrol_target(&self) -> Option<Vec2> {
+        self.patrol
+            .as_ref()
+            .and_then(|route| route.waypoint(self.patrol_index))
+    }
+
+    fn follow_patrol(&mut self, tiles: &TileMap, nav_grid: &NavGrid, dt: f32) {
+        let target = match self.patrol_target() {
+            Some(target) => target,
+            None => return,
+        };
+
+        if self.pos.distance(target) < WAYPOINT_RADIUS {
+            if let Some(route) = &self.patrol {
+                self.patrol_index = route.next_index(self.patrol_index);
             }
         } else {
-            draw_texture_ex(
-                texture,
-                self.pos.x - 12.0,
-                self.pos.y,
-                WHITE,
-                DrawTextureParams {
-                    dest_size: Some(vec2(32.0, 32.0)),
-                    ..Default::default()
-                },
-            );
+            self.travel(target, tiles, nav_grid, None, 0.0, dt);
         }
     }
 
-    pub fn displace(&mut self, other: &Enemy) {
-        if other.pos.distance(self.pos) < 20.0 {
-            let dir = (other.pos() - self.pos()).normalize_or_zero();
-            self.pos += -dir * 10.0 * get_frame_time();
+    pub fn new(pos: Vec2, kind: EnemyKind, rng: &mut GameRng) -> Enemy {
+        Enemy {
+            kind,
+            ai: StateMachine::new(),
+            attack_phase: AttackPhase::Strike,
+            vision: kind.vision(),
+            facing: Vec2::Y,
+            last_seen: None,
+            patrol: None,
+            patrol_index: 0,
+            health_timer: 0.0,
+            health: 3,
+            invulnerable: false,
+            attack_spot: Vec2::ZERO,
+            sword_pos: Vec2::ZERO,
+            prev_sword_pos: Vec2::ZERO,
+            speed: rng.gen_range(90.0, 105.0),
+            move_pos: Vec2::ZERO,
+            pos,
+            prev_pos: pos,
+            prev_goal: None,
+            following_flow: false,
+            path: VecDeque::new(),
         }
     }
 
-    pub fn move_to(&mut self, new_pos: Vec2, tiles: &Vec<Tile>) {
-        if self.pos.distance(new_pos) > 100.0 {
-            self.prev_goal = new_pos;
+    pub fn pos(&self) -> Vec2 {
+        self.pos
+    }
+
+    pub fn update(
+        &mut self,
+        player_pos: Vec2,
+        tiles: &TileMap,
+        nav_grid: &NavGrid,
+        flow_field: Option<&FlowField>,
+        dt: f32,
+    ) -> AiState {
+        self.prev_pos = self.pos;
+        self.prev_sword_pos = self.sword_pos;
+
+        if self.invulnerable {
+            self.health_timer += dt;
+            if self.health_timer > 1.5 {
+                self.health_timer = 0.0;
+                self.invulnerable = false;
+            }
+        }
 
-            self.path = breadth_first_search(
-                calculate_tile_pos(self.pos.x, self.pos.y),
-                calculate_tile_pos(new_pos.x, new_pos.y),
-                tiles,
-            );
+        let visible = self.state() != AiState::Dead && self.look_for(player_pos, nav_grid);
 
-            self.move_pos = self.path.pop_front().unwrap_or(new_pos);
-            self.move_pos = self.move_pos.round();
-        } else {
-            self.path.clear();
-            self.move_pos = new_pos;
+        let perception = Perception {
+            target_visible: visible,
+            target_distance: self.pos.distance(player_pos),
+            has_last_seen: self.last_seen.is_some(),
+            has_patrol: self.patrol.is_some(),
+            health: self.health,
+        };
+
+        let previous = self.ai.state();
+        let state = self.ai.update(&perception, dt);
+
+        let dir = (player_pos - self.pos).normalize_or_zero();
+
+        if state == AiState::Attack && previous != AiState::Attack {
+            self.attack_phase = AttackPhase::Strike;
+            self.attack_spot = self.sword_pos + dir * 40.0;
         }
 
-        if self.pos.distance(new_pos) > 50.0 {
-            if self.pos.distance(self.move_pos) < 2.0 {
-                self.move_pos = self.path.pop_front().unwrap_or(new_pos);
-                self.move_pos = self.move_pos.round();
+        match state {
+            AiState::Idle => self.last_seen = None,
+            AiState::Patrol => {
+                self.last_seen = None;
+                self.follow_patrol(tiles, nav_grid, dt);
+            }
+            AiState::Chase => {
+                self.move_to(player_pos, tiles, nav_grid, flow_field, dt);
+                self.sword_pos = self.pos + dir * 20.0;
             }
+            AiState::Attack => self.update_attack(dir, dt),
+            AiState::Search => self.investigate(tiles, nav_grid, dt),
+            AiState::Flee => self.flee_from(player_pos, tiles, dt),
+            AiState::Dead => (),
+        }
 
-            let mut velocity = (self.move_pos - self.pos).normalize_or_zero();
+        self.ai.state()
+    }
 
-            let mut new_pos = self.pos + velocity * self.speed * get_frame_time();
+    fn update_attack(&mut self, dir: Vec2, dt: f32) {
+        let rest = self.pos + dir * 20.0;
 
-            if velocity.x <= 0.0 {
-                if get_tile(new_pos.x - 15.0, self.pos.y, tiles) {
-                    new_pos.x = self.pos.x;
-                    velocity.x = 0.0;
-                }
-            } else {
-                if get_tile(new_pos.x + 15.0, self.pos.y, tiles) {
-                    new_pos.x = self.pos.x;
-                    velocity.x = 0.0;
+        match self.attack_phase {
+            AttackPhase::Strike => {
+                self.sword_pos = self.sword_pos.lerp(self.attack_spot, damp(0.06, dt));
+                if self.sword_pos.distance(self.attack_spot) < 5.0 {
+                    self.attack_phase = AttackPhase::Recover;
                 }
             }
-
-            if velocity.y <= 0.0 {
-                if get_tile(self.pos.x, new_pos.y - 15.0, tiles) {
-                    new_pos.y = self.pos.y;
-                    velocity.y = 0.0;
-                }
-            } else {
-                if get_tile(self.pos.x, new_pos.y + 15.0, tiles) {
-                    new_pos.y = self.pos.y;
-                    velocity.y = 0.0;
+            AttackPhase::Recover => {
+                self.sword_pos = self.sword_pos.lerp(rest, damp(0.1, dt));
+                if self.sword_pos.distance(rest) < 5.0 {
+                    self.ai.finish_attack();
                 }
             }
+        }
 
-            self.pos = new_pos;
+        if self.sword_pos.distance(self.pos) > 50.0 {
+            self.sword_pos = rest;
+            self.ai.finish_attack();
         }
     }
-}
 
-pub struct Path {
-    a: Option<Vec2>,
-    b: Vec2,
-}
+    fn flee_from(&mut self, threat: Vec2, tiles: &TileMap, dt: f32) {
+        self.prev_goal = None;
+        self.following_flow = false;
+        self.path.clear();
 
-impl Path {
-    pub fn new(a: Option<Vec2>, b: Vec2) -> Path {
-        Path { a, b }
+        let away = (self.pos - threat).normalize_or_zero();
+        self.step_towards(self.pos + away * TILE_SIZE, tiles, dt);
     }
-}
 
-pub fn breadth_first_search(start_pos: Vec2, goal: Vec2, tiles: &Vec<Tile>) -> VecDeque<Vec2> {
-    if get_tile(goal.x, goal.y, tiles) {
-        return VecDeque::new();
+    pub fn damage_player(&self, player: &mut Player) {
+        if self.state() == AiState::Attack
+            && self.attack_phase == AttackPhase::Strike
+            && self.sword_pos.distance(player.pos()) < 19.0
+        {
+            player.take_damage();
+        }
     }
 
-    let mut frontier: VecDeque<Vec2> = VecDeque::new();
-    frontier.push_front(start_pos);
-
-    let mut came_from: Vec<Path> = Vec::new();
-    came_from.push(Path::new(None, start_pos));
-
-    while !frontier.is_empty() {
-        let current = frontier.pop_back().unwrap_or(start_pos);
+    pub fn take_damage(&mut self) {
+        if !self.invulnerable {
+            self.health -= 1;
+            self.health = self.health.max(0);
 
-        if current == goal {
-            break;
+            self.ai.interrupt();
+            self.invulnerable = true;
         }
+    }
 
-        for next in get_neighbors(current, tiles).iter() {
-            if !is_found_in_vec(&came_from, *next) {
-                frontier.push_front(*next);
-                came_from.push(Path::new(Some(current), *next));
-            }
+    pub fn displace(&mut self, other: &Enemy, dt: f32) {
+        if other.pos.distance(self.pos) < 20.0 {
+            let dir = (other.pos() - self.pos()).normalize_or_zero();
+            self.pos += -dir * 10.0 * dt;
         }
     }
 
-    let mut current = goal;
-    let mut path: VecDeque<Vec2> = VecDeque::new();
-
-    while current != start_pos {
-        path.push_front(current);
-        if let Some(a) = get_path(&came_from, current) {
-            current = a;
-        }
+    pub fn move_to(
+        &mut self,
+        new_pos: Vec2,
+        tiles: &TileMap,
+        nav_grid: &NavGrid,
+        flow_field: Option<&FlowField>,
+        dt: f32,
+    ) {
+        self.travel(new_pos, tiles, nav_grid, flow_field, 50.0, dt);
     }
 
-    path
-}
+    fn travel(
+        &mut self,
+        new_pos: Vec2,
+        tiles: &TileMap,
+        nav_grid: &NavGrid,
+        flow_field: Option<&FlowField>,
+        stop_distance: f32,
+        dt: f32,
+    ) {
+        let flow_field = flow_field
+            .filter(|field| field.mode() == self.kind.neighbor_mode() && field.targets(new_pos));
+
+        let direct =
+            self.pos.distance(new_pos) <= 100.0 && nav_grid.can_traverse(self.pos, new_pos);
+
+        if !direct {
+            let goal = calculate_tile_pos(new_pos.x, new_pos.y);
+
+            if let Some(field) = flow_field {
+                if !self.following_flow {
+                    self.following_flow = true;
+                    self.prev_goal = None;
+                    self.path.clear();
+
+                    self.move_pos = field.next_step(self.pos).unwrap_or(new_pos);
+                    self.move_pos = self.move_pos.round();
+                }
+            } else if self.prev_goal != Some(goal) || self.following_flow {
+                self.following_flow = false;
+                self.prev_goal = Some(goal);
+
+                self.path = nav_grid
+                    .find_path(
+                        self.pos,
+                        new_pos,
+                        self.kind.neighbor_mode(),
+                        MAX_PATH_EXPANSIONS,
+                    )
+                    .map(|path| {
+                        let funneled = nav_grid.funnel_path(self.pos, &path, FUNNEL_TOLERANCE);
+                        nav_grid.smooth_path(self.pos, &funneled).into_waypoints()
+                    })
+                    .unwrap_or_default();
 
-pub fn get_path(vector: &Vec<Path>, b: Vec2) -> Option<Vec2> {
-    for v in vector.iter() {
-        if v.b == b {
-            return v.a;
+                self.move_pos = self.path.pop_front().unwrap_or(new_pos);
+                self.move_pos = self.move_pos.round();
+            }
+        } else {
+            self.following_flow = false;
+            self.prev_goal = None;
+            self.path.clear();
+            self.move_pos = new_pos;
         }
-    }
 
-    None
-}
+        if self.pos.distance(new_pos) > stop_distance {
+            if self.pos.distance(self.move_pos) < 2.0 {
+                let next = match flow_field {
+                    Some(field) if self.following_flow => field.next_step(self.move_pos),
+                    _ => self.path.pop_front(),
+                };
 
-pub fn is_found_in_vec(vector: &Vec<Path>, element: Vec2) -> bool {
-    for v in vector.iter() {
-        if v.b == element {
-            return true;
+                self.move_pos = next.unwrap_or(new_pos);
+                self.move_pos = self.move_pos.round();
+            }
+
+            self.step_towards(self.move_pos, tiles, dt);
         }
     }
 
-    false
-}
+    fn step_towards(&mut self, target: Vec2, tiles: &TileMap, dt: f32) {
+        let velocity = (target - self.pos).normalize_or_zero();
+        self.set_facing(velocity);
 
-pub fn get_neighbors(tile: Vec2, tiles: &Vec<Tile>) -> Vec<Vec2> {
-    let up = get_tile(tile.x, tile.y - 32.0, tiles);
-    let down = get_tile(tile.x, tile.y + 32.0, tiles);
-    let right = get_tile(tile.x + 32.0, tile.y, tiles);
-    let left = get_tile(tile.x - 32.0, tile.y, tiles);
+        let step = (self.speed * dt).min(self.pos.distance(target));
+        let mut new_pos = self.pos + velocity * step;
 
-    let mut neighbors = Vec::new();
+        if velocity.x <= 0.0 {
+            if get_tile(new_pos.x - 15.0, self.pos.y, tiles) {
+                new_pos.x = self.pos.x;
+            }
+        } else if get_tile(new_pos.x + 15.0, self.pos.y, tiles) {
+            new_pos.x = self.pos.x;
+        }
 
-    if !up {
-        neighbors.push(calculate_tile_pos(tile.x, tile.y - 32.0));
-    }
-    if !down {
-        neighbors.push(calculate_tile_pos(tile.x, tile.y + 32.0));
-    }
-    if !right {
-        neighbors.push(calculate_tile_pos(tile.x + 32.0, tile.y));
-    }
-    if !left {
-        neighbors.push(calculate_tile_pos(tile.x - 32.0, tile.y));
-    }
+        if velocity.y <= 0.0 {
+            if get_tile(self.pos.x, new_pos.y - 15.0, tiles) {
+                new_pos.y = self.pos.y;
+            }
+        } else if get_tile(self.pos.x, new_pos.y + 15.0, tiles) {
+            new_pos.y = self.pos.y;
+        }
 
-    neighbors
+        self.pos = new_pos;
+    }
 }
@@ -0,0 +1,166 @@
+pub const ATTACK_RANGE: f32 = 50.0;
+pub const ATTACK_COOLDOWN: f32 = 2.5;
+pub const ATTACK_TIMEOUT: f32 = 1.5;
+pub const SEARCH_DURATION: f32 = 5.0;
+pub const FLEE_HEALTH: i32 = 1;
+pub const FLEE_DISTANCE: f32 = 200.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AiState {
+    Idle,
+    Patrol,
+    Chase,
+    Attack,
+    Search,
+    Flee,
+    Dead,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Perception {
+    pub target_visible: bool,
+    pub target_distance: f32,
+    pub has_last_seen: bool,
+    pub has_patrol: bool,
+    pub health: i32,
+}
+
+#[derive(Clone)]
+pub struct StateMachine {
+    state: AiState,
+    time_in_state: f32,
+    attack_cooldown: f32,
+}
+
+impl Default for StateMachine {
+    fn default() -> Self {
+        StateMachine::new()
+    }
+}
+
+impl StateMachine {
+    pub fn new() -> StateMachine {
+        StateMachine {
+            state: AiState::Idle,
+            time_in_state: 0.0,
+            attack_cooldown: 0.5,
+        }
+    }
+
+    pub fn state(&self) -> AiState {
+        self.state
+    }
+
+    pub fn time_in_state(&self) -> f32 {
+        self.time_in_state
+    }
+
+    pub fn attack_ready(&self) -> bool {
+        self.attack_cooldown <= 0.0
+    }
+
+    pub fn reset_cooldown(&mut self) {
+        self.attack_cooldown = ATTACK_COOLDOWN;
+    }
+
+    pub fn finish_attack(&mut self) {
+        if self.state == AiState::Attack {
+            self.reset_cooldown();
+            self.transition(AiState::Chase);
+        }
+    }
+
+    pub fn interrupt(&mut self) {
+        self.reset_cooldown();
+        if self.state == AiState::Attack {
+            self.transition(AiState::Chase);
+        }
+    }
+
+    fn transition(&mut self, state: AiState) {
+        if self.state != state {
+            self.state = state;
+            self.time_in_state = 0.0;
+        }
+    }
+
+    fn calm_state(perception: &Perception) -> AiState {
+        if perception.has_patrol {
+            AiState::Patrol
+        } else {
+            AiState::Idle
+        }
+    }
+
+    pub fn update(&mut self, perception: &Perception, dt: f32) -> AiState {
+        self.time_in_state += dt;
+        self.attack_cooldown = (self.attack_cooldown - dt).max(0.0);
+
+        if self.state == AiState::Dead {
+            return self.state;
+        }
+
+        if perception.health <= 0 {
+            self.transition(AiState::Dead);
+            return self.state;
+        }
+
+        let threatened = perception.target_visible && perception.target_distance < FLEE_DISTANCE;
+        if perception.health <= FLEE_HEALTH && threatened && self.state != AiState::Flee {
+            self.transition(AiState::Flee);
+            return self.state;
+        }
+
+        let next = match self.state {
+            AiState::Idle | AiState::Patrol => {
+                if perception.target_visible {
+                    AiState::Chase
+                } else {
+                    StateMachine::calm_state(perception)
+                }
+            }
+            AiState::Chase => {
+                if !perception.target_visible {
+                    AiState::Search
+                } else if perception.target_distance <= ATTACK_RANGE && self.attack_ready() {
+                    AiState::Attack
+                } else {
+                    AiState::Chase
+                }
+            }
+            AiState::Attack => {
+                if self.time_in_state > ATTACK_TIMEOUT {
+                    self.reset_cooldown();
+                    AiState::Chase
+                } else {
+                    AiState::Attack
+                }
+            }
+            AiState::Search => {
+                if perception.target_visible {
+                    AiState::Chase
+                } else if !perception.has_last_seen || self.time_in_state > SEARCH_DURATION {
+                    StateMachine::calm_state(perception)
+                } else {
+                    AiState::Search
+                }
+            }
+            AiState::Flee => {
+                if perception.health > FLEE_HEALTH {
+                    AiState::Chase
+                } else if !perception.target_visible
+                    && (perception.target_distance >= FLEE_DISTANCE
+                        || self.time_in_state > SEARCH_DURATION)
+                {
+                    StateMachine::calm_state(perception)
+                } else {
+                    AiState::Flee
+                }
+            }
+            AiState::Dead => AiState::Dead,
+        };
+
+        self.transition(next);
+        self.state
+    }
+}
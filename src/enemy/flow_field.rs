@@ -0,0 +1,118 @@
+use crate::enemy::navigation::*;
+use crate::tile::*;
+
+use macroquad::prelude::*;
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+pub struct FlowField {
+    mode: NeighborMode,
+    goal: Option<(i32, i32)>,
+    width: i32,
+    height: i32,
+    distances: Vec<u32>,
+    next: Vec<Option<(i32, i32)>>,
+}
+
+impl FlowField {
+    pub fn new(mode: NeighborMode) -> FlowField {
+        FlowField {
+            mode,
+            goal: None,
+            width: 0,
+            height: 0,
+            distances: Vec::new(),
+            next: Vec::new(),
+        }
+    }
+
+    pub fn mode(&self) -> NeighborMode {
+        self.mode
+    }
+
+    pub fn goal(&self) -> Option<(i32, i32)> {
+        self.goal
+    }
+
+    pub fn clear(&mut self) {
+        self.goal = None;
+        self.distances.clear();
+        self.next.clear();
+    }
+
+    pub fn targets(&self, pos: Vec2) -> bool {
+        self.goal == Some(world_to_grid(pos))
+    }
+
+    pub fn update(&mut self, nav_grid: &NavGrid, target: Vec2) -> bool {
+        let goal = world_to_grid(target);
+
+        if self.goal == Some(goal) {
+            return false;
+        }
+
+        self.rebuild(nav_grid, goal);
+        true
+    }
+
+    pub fn rebuild(&mut self, nav_grid: &NavGrid, goal: (i32, i32)) {
+        self.goal = Some(goal);
+        self.width = nav_grid.width();
+        self.height = nav_grid.height();
+
+        let size = (self.width * self.height) as usize;
+        self.distances = vec![u32::MAX; size];
+        self.next = vec![None; size];
+
+        let goal_index = match nav_grid.index(goal.0, goal.1) {
+            Some(index) if nav_grid.is_walkable(goal.0, goal.1) => index,
+            _ => return,
+        };
+
+        let mut open = BinaryHeap::new();
+        self.distances[goal_index] = 0;
+        open.push(Reverse((0, goal_index)));
+
+        while let Some(Reverse((distance, index))) = open.pop() {
+            if distance > self.distances[index] {
+                continue;
+            }
+
+            let (col, row) = nav_grid.cell(index);
+            for (next_col, next_row) in nav_grid.neighbors(col, row, self.mode) {
+                let next_index = (next_row * self.width + next_col) as usize;
+                let next_distance =
+                    distance + self.mode.step_cost((col, row), (next_col, next_row));
+
+                if next_distance < self.distances[next_index] {
+                    self.distances[next_index] = next_distance;
+                    self.next[next_index] = Some((col, row));
+                    open.push(Reverse((next_distance, next_index)));
+                }
+            }
+        }
+    }
+
+    fn index(&self, pos: Vec2) -> Option<usize> {
+        let (col, row) = world_to_grid(pos);
+
+        if col >= 0 && row >= 0 && col < self.width && row < self.height {
+            Some((row * self.width + col) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn distance(&self, pos: Vec2) -> Option<u32> {
+        self.index(pos)
+            .map(|index| self.distances[index])
+            .filter(|distance| *distance != u32::MAX)
+    }
+
+    pub fn next_step(&self, pos: Vec2) -> Option<Vec2> {
+        self.index(pos)
+            .and_then(|index| self.next[index])
+            .map(|(col, row)| grid_to_world(col, row))
+    }
+}
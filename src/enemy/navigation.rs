@@ -0,0 +1,427 @@
+use crate::tile::*;
+
+use macroquad::prelude::*;
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+pub const MAX_PATH_EXPANSIONS: usize = 4096;
+
+pub const ORTHOGONAL_COST: u32 = 10;
+pub const DIAGONAL_COST: u32 = 14;
+
+pub const AGENT_HALF_EXTENT: f32 = 15.0;
+pub const FUNNEL_TOLERANCE: f32 = 8.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NeighborMode {
+    Four,
+    Eight { cut_corners: bool },
+}
+
+impl NeighborMode {
+    pub fn step_cost(&self, from: (i32, i32), to: (i32, i32)) -> u32 {
+        if from.0 != to.0 && from.1 != to.1 {
+            DIAGONAL_COST
+        } else {
+            ORTHOGONAL_COST
+        }
+    }
+
+    pub fn heuristic(&self, from: (i32, i32), to: (i32, i32)) -> u32 {
+        let dx = (from.0 - to.0).unsigned_abs();
+        let dy = (from.1 - to.1).unsigned_abs();
+
+        match self {
+            NeighborMode::Four => ORTHOGONAL_COST * (dx + dy),
+            NeighborMode::Eight { .. } => {
+                ORTHOGONAL_COST * dx.max(dy) + (DIAGONAL_COST - ORTHOGONAL_COST) * dx.min(dy)
+            }
+        }
+    }
+}
+
+pub struct Path {
+    waypoints: VecDeque<Vec2>,
+}
+
+impl Path {
+    pub fn new(waypoints: VecDeque<Vec2>) -> Path {
+        Path { waypoints }
+    }
+
+    pub fn waypoints(&self) -> &VecDeque<Vec2> {
+        &self.waypoints
+    }
+
+    pub fn into_waypoints(self) -> VecDeque<Vec2> {
+        self.waypoints
+    }
+
+    pub fn len(&self) -> usize {
+        self.waypoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.waypoints.is_empty()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct OpenNode {
+    f_score: u32,
+    g_score: u32,
+    index: usize,
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, so the comparison is reversed to pop the cheapest node first.
+        other
+            .f_score
+            .cmp(&self.f_score)
+            .then_with(|| self.g_score.cmp(&other.g_score))
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub struct NavGrid {
+    width: i32,
+    height: i32,
+    walkable: Vec<bool>,
+}
+
+impl NavGrid {
+    pub fn from_tile_map(tiles: &TileMap) -> NavGrid {
+        let walkable = tiles
+            .cells()
+            .map(|(col, row, _)| !tiles.is_wall(col, row))
+            .collect();
+
+        NavGrid {
+            width: tiles.width(),
+            height: tiles.height(),
+            walkable,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn index(&self, col: i32, row: i32) -> Option<usize> {
+        if col >= 0 && row >= 0 && col < self.width && row < self.height {
+            Some((row * self.width + col) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn cell(&self, index: usize) -> (i32, i32) {
+        (index as i32 % self.width, index as i32 / self.width)
+    }
+
+    pub fn is_walkable(&self, col: i32, row: i32) -> bool {
+        self.index(col, row)
+            .map(|index| self.walkable[index])
+            .unwrap_or(false)
+    }
+
+    pub fn neighbors(&self, col: i32, row: i32, mode: NeighborMode) -> Vec<(i32, i32)> {
+        let mut neighbors: Vec<(i32, i32)> = [(0, -1), (0, 1), (1, 0), (-1, 0)]
+            .iter()
+            .map(|(x, y)| (col + x, row + y))
+            .filter(|(x, y)| self.is_walkable(*x, *y))
+            .collect();
+
+        if let NeighborMode::Eight { cut_corners } = mode {
+            for (x, y) in [(1, -1), (1, 1), (-1, 1), (-1, -1)] {
+                if !self.is_walkable(col + x, row + y) {
+                    continue;
+                }
+
+                let horizontal = self.is_walkable(col + x, row);
+                let vertical = self.is_walkable(col, row + y);
+
+                let allowed = if cut_corners {
+                    horizontal || vertical
+                } else {
+                    horizontal && vertical
+                };
+
+                if allowed {
+                    neighbors.push((col + x, row + y));
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    pub fn find_path(
+        &self,
+        start: Vec2,
+        goal: Vec2,
+        mode: NeighborMode,
+        max_expansions: usize,
+    ) -> Option<Path> {
+        let (start_col, start_row) = world_to_grid(start);
+        let (goal_col, goal_row) = world_to_grid(goal);
+
+        let start_index = self.index(start_col, start_row)?;
+        let goal_index = self.index(goal_col, goal_row)?;
+
+        if !self.walkable[goal_index] {
+            return None;
+        }
+
+        let heuristic = |col: i32, row: i32| mode.heuristic((col, row), (goal_col, goal_row));
+
+        let mut open = BinaryHeap::new();
+        let mut closed: HashSet<usize> = HashSet::new();
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut g_scores: HashMap<usize, u32> = HashMap::new();
+
+        g_scores.insert(start_index, 0);
+        open.push(OpenNode {
+            f_score: heuristic(start_col, start_row),
+            g_score: 0,
+            index: start_index,
+        });
+
+        let mut expansions = 0;
+
+        while let Some(current) = open.pop() {
+            if current.index == goal_index {
+                return Some(self.reconstruct_path(&came_from, start_index, goal_index));
+            }
+
+            if !closed.insert(current.index) {
+                continue;
+            }
+
+            expansions += 1;
+            if expansions > max_expansions {
+                return None;
+            }
+
+            let (col, row) = self.cell(current.index);
+            for (next_col, next_row) in self.neighbors(col, row, mode) {
+                let next_index = (next_row * self.width + next_col) as usize;
+                if closed.contains(&next_index) {
+                    continue;
+                }
+
+                let g_score = current.g_score + mode.step_cost((col, row), (next_col, next_row));
+                let known = g_scores.get(&next_index).copied().unwrap_or(u32::MAX);
+
+                if g_score < known {
+                    g_scores.insert(next_index, g_score);
+                    came_from.insert(next_index, current.index);
+                    open.push(OpenNode {
+                        f_score: g_score + heuristic(next_col, next_row),
+                        g_score,
+                        index: next_index,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn reconstruct_path(
+        &self,
+        came_from: &HashMap<usize, usize>,
+        start_index: usize,
+        goal_index: usize,
+    ) -> Path {
+        let mut waypoints = VecDeque::new();
+        let mut current = goal_index;
+
+        while current != start_index {
+            let (col, row) = self.cell(current);
+            waypoints.push_front(grid_to_world(col, row));
+
+            match came_from.get(&current) {
+                Some(previous) => current = *previous,
+                None => break,
+            }
+        }
+
+        Path::new(waypoints)
+    }
+
+    pub fn raycast(&self, from: Vec2, to: Vec2) -> Option<(i32, i32)> {
+        const EPSILON: f32 = 1e-5;
+
+        let (mut col, mut row) = world_to_grid(from);
+        if !self.is_walkable(col, row) {
+            return Some((col, row));
+        }
+
+        let delta = to - from;
+        let step_x = delta.x.signum() as i32 * (delta.x != 0.0) as i32;
+        let step_y = delta.y.signum() as i32 * (delta.y != 0.0) as i32;
+
+        let t_delta_x = if step_x != 0 {
+            TILE_SIZE / delta.x.abs()
+        } else {
+            f32::INFINITY
+        };
+        let t_delta_y = if step_y != 0 {
+            TILE_SIZE / delta.y.abs()
+        } else {
+            f32::INFINITY
+        };
+
+        let mut t_max_x = match step_x {
+            1 => ((col + 1) as f32 * TILE_SIZE - from.x) / delta.x,
+            -1 => (col as f32 * TILE_SIZE - from.x) / delta.x,
+            _ => f32::INFINITY,
+        };
+        let mut t_max_y = match step_y {
+            1 => ((row + 1) as f32 * TILE_SIZE - from.y) / delta.y,
+            -1 => (row as f32 * TILE_SIZE - from.y) / delta.y,
+            _ => f32::INFINITY,
+        };
+
+        while t_max_x.min(t_max_y) < 1.0 {
+            if (t_max_x - t_max_y).abs() < EPSILON {
+                // The ray passes exactly through a tile corner, so both side cells have to be open.
+                if !self.is_walkable(col + step_x, row) {
+                    return Some((col + step_x, row));
+                }
+                if !self.is_walkable(col, row + step_y) {
+                    return Some((col, row + step_y));
+                }
+
+                col += step_x;
+                row += step_y;
+                t_max_x += t_delta_x;
+                t_max_y += t_delta_y;
+            } else if t_max_x < t_max_y {
+                col += step_x;
+                t_max_x += t_delta_x;
+            } else {
+                row += step_y;
+                t_max_y += t_delta_y;
+            }
+
+            if !self.is_walkable(col, row) {
+                return Some((col, row));
+            }
+        }
+
+        None
+    }
+
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        self.raycast(from, to).is_none()
+    }
+
+    pub fn sweep_clear(&self, from: Vec2, to: Vec2, half_extent: f32) -> bool {
+        [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+            .iter()
+            .all(|(x, y)| {
+                let offset = vec2(*x, *y) * half_extent;
+                self.line_of_sight(from + offset, to + offset)
+            })
+    }
+
+    pub fn can_traverse(&self, from: Vec2, to: Vec2) -> bool {
+        let center = Vec2::splat(AGENT_HALF_EXTENT);
+        self.sweep_clear(from + center, to + center, AGENT_HALF_EXTENT)
+    }
+
+    pub fn smooth_path(&self, start: Vec2, path: &Path) -> Path {
+        let waypoints = path.waypoints();
+        let mut smoothed = VecDeque::new();
+
+        let mut anchor = start;
+        let mut index = 0;
+
+        while index < waypoints.len() {
+            let mut furthest = index;
+            for next in (index + 1..waypoints.len()).rev() {
+                if self.can_traverse(anchor, waypoints[next]) {
+                    furthest = next;
+                    break;
+                }
+            }
+
+            anchor = waypoints[furthest];
+            smoothed.push_back(anchor);
+            index = furthest + 1;
+        }
+
+        Path::new(smoothed)
+    }
+
+    pub fn funnel_path(&self, start: Vec2, path: &Path, tolerance: f32) -> Path {
+        let waypoints = path.waypoints();
+        let mut simplified = VecDeque::new();
+
+        let mut apex = start;
+        let mut funnel: Option<(Vec2, Vec2)> = None;
+
+        let mut index = 0;
+        while index < waypoints.len() {
+            let direction = waypoints[index] - apex;
+            let offset = direction.perp().normalize_or_zero() * tolerance;
+            let (right, left) = (direction - offset, direction + offset);
+
+            let inside = match funnel {
+                None => true,
+                Some((low, high)) => {
+                    low.perp_dot(direction) >= 0.0 && direction.perp_dot(high) >= 0.0
+                }
+            };
+
+            if inside && self.can_traverse(apex, waypoints[index]) {
+                funnel = Some(match funnel {
+                    None => (right, left),
+                    Some((low, high)) => (
+                        if low.perp_dot(right) > 0.0 {
+                            right
+                        } else {
+                            low
+                        },
+                        if high.perp_dot(left) < 0.0 {
+                            left
+                        } else {
+                            high
+                        },
+                    ),
+                });
+                index += 1;
+            } else if index > 0 && funnel.is_some() {
+                apex = waypoints[index - 1];
+                simplified.push_back(apex);
+                funnel = None;
+            } else {
+                apex = waypoints[index];
+                simplified.push_back(apex);
+                index += 1;
+            }
+        }
+
+        if let Some(last) = waypoints.back() {
+            if simplified.back() != Some(last) {
+                simplified.push_back(*last);
+            }
+        }
+
+        Path::new(simplified)
+    }
+}
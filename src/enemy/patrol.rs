@@ -0,0 +1,44 @@
+use macroquad::prelude::*;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatrolRoute {
+    waypoints: Vec<Vec2>,
+}
+
+impl PatrolRoute {
+    pub fn new(waypoints: Vec<Vec2>) -> PatrolRoute {
+        PatrolRoute { waypoints }
+    }
+
+    pub fn waypoints(&self) -> &[Vec2] {
+        &self.waypoints
+    }
+
+    pub fn waypoint(&self, index: usize) -> Option<Vec2> {
+        self.waypoints.get(index).copied()
+    }
+
+    pub fn push(&mut self, waypoint: Vec2) {
+        self.waypoints.push(waypoint);
+    }
+
+    pub fn pop(&mut self) -> Option<Vec2> {
+        self.waypoints.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.waypoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.waypoints.is_empty()
+    }
+
+    pub fn next_index(&self, index: usize) -> usize {
+        if self.waypoints.is_empty() {
+            0
+        } else {
+            (index + 1) % self.waypoints.len()
+        }
+    }
+}
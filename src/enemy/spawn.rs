@@ -0,0 +1,69 @@
+use crate::enemy::*;
+use crate::tile::*;
+
+use macroquad::prelude::*;
+
+pub const MAX_SPAWN_COUNT: u32 = 10;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EnemySpawn {
+    pos: Vec2,
+    kind: EnemyKind,
+    count: u32,
+}
+
+impl EnemySpawn {
+    pub fn new(pos: Vec2, kind: EnemyKind, count: u32) -> EnemySpawn {
+        EnemySpawn {
+            pos,
+            kind,
+            count: count.clamp(1, MAX_SPAWN_COUNT),
+        }
+    }
+
+    pub fn pos(&self) -> Vec2 {
+        self.pos
+    }
+
+    pub fn kind(&self) -> EnemyKind {
+        self.kind
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn set_kind(&mut self, kind: EnemyKind) {
+        self.kind = kind;
+    }
+
+    pub fn set_count(&mut self, count: u32) {
+        self.count = count.clamp(1, MAX_SPAWN_COUNT);
+    }
+
+    pub fn spawn_positions(&self, tiles: &TileMap) -> Vec<Vec2> {
+        let (col, row) = tiles.world_to_grid(self.pos);
+        let mut positions = Vec::new();
+
+        let max_radius = tiles.width().max(tiles.height());
+        for radius in 0..=max_radius {
+            for y in -radius..=radius {
+                for x in -radius..=radius {
+                    if x.abs() != radius && y.abs() != radius {
+                        continue;
+                    }
+
+                    if positions.len() as u32 >= self.count {
+                        return positions;
+                    }
+
+                    if tiles.is_floor(col + x, row + y) {
+                        positions.push(tiles.grid_to_world(col + x, row + y));
+                    }
+                }
+            }
+        }
+
+        positions
+    }
+}
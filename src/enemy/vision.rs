@@ -0,0 +1,53 @@
+use crate::enemy::navigation::*;
+
+use macroquad::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vision {
+    range: f32,
+    fov: f32,
+}
+
+impl Vision {
+    pub fn new(range: f32, fov: f32) -> Vision {
+        Vision {
+            range: range.max(0.0),
+            fov: fov.clamp(0.0, std::f32::consts::TAU),
+        }
+    }
+
+    pub fn range(&self) -> f32 {
+        self.range
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn set_range(&mut self, range: f32) {
+        self.range = range.max(0.0);
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov.clamp(0.0, std::f32::consts::TAU);
+    }
+
+    pub fn in_cone(&self, origin: Vec2, facing: Vec2, target: Vec2) -> bool {
+        let to_target = target - origin;
+        let distance = to_target.length();
+
+        if distance > self.range {
+            return false;
+        }
+
+        if distance <= f32::EPSILON || facing == Vec2::ZERO {
+            return true;
+        }
+
+        facing.angle_between(to_target).abs() <= self.fov * 0.5
+    }
+
+    pub fn can_see(&self, origin: Vec2, facing: Vec2, target: Vec2, nav_grid: &NavGrid) -> bool {
+        self.in_cone(origin, facing, target) && nav_grid.line_of_sight(origin, target)
+    }
+}
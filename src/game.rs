@@ -1,5 +1,9 @@
-use dungeon_game::{editor::tilemap_editor::*, enemy::*, player::*, utility::*};
+use dungeon_game::{
+    editor::tilemap_editor::*, input::gamepad::*, input::*, renderer::*, replay::*, rng::*,
+    tile::*, timestep::*, utility::*, world::*,
+};
 use macroquad::prelude::*;
+use macroquad::ui::root_ui;
 use std::process;
 
 use std::collections::VecDeque;
@@ -7,35 +11,42 @@ use std::collections::VecDeque;
 pub enum GameState {
     EditorMode,
     Play,
+    Replay,
 }
 
 pub struct Game {
     game_state: GameState,
-    current_level: i32,
     game_camera: Camera2D,
-    player_sword_sprite: Texture2D,
     debug_collision: bool,
-    enemies: Vec<Enemy>,
-    player: Player,
-    enemy_sword_sprite: Texture2D,
-    enemy_sprite_a: Texture2D,
-    level_atlas: Texture2D,
+    world: World,
+    renderer: Renderer,
+    seed: u64,
+    seeds: Option<GameRng>,
+    timestep: FixedTimestep,
+    input: InputSnapshot,
+    input_map: InputMap,
+    gamepad: Gamepad,
+    aim_tracker: AimTracker,
+    recording: Option<Recording>,
+    replay_dir: String,
+    replay: Option<Replay>,
     editor: TileMapEditor,
     _debug_path: VecDeque<Vec2>,
 }
 
 impl Game {
-    pub async fn build() -> Game {
-        let mut player = Player::new(20.0, 20.0, 32.0, 32.0, 100.0);
-
-        player.set_texture(
-            create_texture("res/textures/player.png")
-                .await
-                .unwrap_or_else(|err| {
-                    println!("{err}");
-                    process::exit(1);
-                }),
-        );
+    pub async fn build(
+        seed: Option<u64>,
+        replay_path: Option<String>,
+        replay_dir: Option<String>,
+        levels_dir: Option<String>,
+    ) -> Game {
+        let player_sprite = create_texture("res/textures/player.png")
+            .await
+            .unwrap_or_else(|err| {
+                println!("{err}");
+                process::exit(1);
+            });
 
         let enemy_sprite_a = create_texture("res/textures/sword_enemy.png")
             .await
@@ -66,20 +77,59 @@ impl Game {
             });
 
         let mut editor = TileMapEditor::new(0.0, 0.0, 1.0, 1.0, 16.0, (10, 10), (1.0, 1.0));
-        editor.set_texture(level_atlas.clone());
+        editor.set_texture(level_atlas);
+        if let Some(dir) = levels_dir {
+            editor.set_levels_dir(&dir);
+        }
 
         let player_cam = Camera2D::from_display_rect(Rect::new(0.0, 0.0, 600.0, 600.0));
 
+        let mut seeds = match seed {
+            Some(_) => None,
+            None => Some(GameRng::from_time()),
+        };
+        let mut seed = seed.unwrap_or_else(|| seeds.as_mut().map_or(0, GameRng::next_seed));
+        let mut game_state = GameState::EditorMode;
+        let mut world = World::new(TileMap::new(0, 0), seed);
+        let mut replay = None;
+
+        if let Some(path) = replay_path {
+            match Recording::load(&path).and_then(|recording| {
+                let world = recording.build_world()?;
+                Ok((recording, world))
+            }) {
+                Ok((recording, replay_world)) => {
+                    seed = recording.seed();
+                    seeds = None;
+                    world = replay_world;
+                    game_state = GameState::Replay;
+                    replay = Some(Replay::new(recording));
+                }
+                Err(err) => eprintln!("Could not replay {}: {}", path, err),
+            }
+        }
+
         Game {
-            current_level: 0,
-            player_sword_sprite,
-            game_state: GameState::EditorMode,
+            game_state,
             debug_collision: true,
-            enemies: Vec::new(),
-            player,
-            enemy_sprite_a,
-            enemy_sword_sprite,
-            level_atlas,
+            world,
+            renderer: Renderer::new(
+                player_sprite,
+                player_sword_sprite,
+                enemy_sprite_a,
+                enemy_sword_sprite,
+                level_atlas,
+            ),
+            seed,
+            seeds,
+            timestep: FixedTimestep::default(),
+            input: InputSnapshot::default(),
+            input_map: InputMap::load(INPUT_CONFIG_PATH),
+            gamepad: Gamepad::new(),
+            aim_tracker: AimTracker::new(),
+            recording: None,
+            replay_dir: replay_dir.unwrap_or_else(default_replay_dir),
+            replay,
             game_camera: player_cam,
             editor,
             _debug_path: VecDeque::new(),
@@ -87,103 +137,173 @@ impl Game {
     }
 
     pub fn update(&mut self) {
-        if is_key_pressed(KeyCode::Tab) && is_key_down(KeyCode::LeftControl) {
+        self.gamepad.poll();
+
+        if self.input_map.pressed(Action::ToggleEditor) {
             if let GameState::EditorMode = self.game_state {
+                if !self.editor.ready_to_play() {
+                    return;
+                }
+            }
+
+            self.finish_recording();
+
+            if let GameState::EditorMode = self.game_state {
+                if let Some(seeds) = self.seeds.as_mut() {
+                    self.seed = seeds.next_seed();
+                }
+
                 self.game_state = GameState::Play;
+                self.recording = match self.editor.current_file() {
+                    Some(_) if self.editor.has_unsaved_changes() => {
+                        eprintln!("Level has unsaved changes, run will not be recorded.");
+                        None
+                    }
+                    Some(level) => match Recording::record(
+                        self.editor.levels_dir(),
+                        level,
+                        self.seed,
+                        self.editor.random_spawn_fallback,
+                    ) {
+                        Ok(recording) => Some(recording),
+                        Err(err) => {
+                            eprintln!("Run will not be recorded: {}", err);
+                            None
+                        }
+                    },
+                    None => {
+                        eprintln!("Level not saved, run will not be recorded.");
+                        None
+                    }
+                };
             } else {
                 self.game_state = GameState::EditorMode;
+                self.replay = None;
             }
 
-            self.enemies.clear();
+            self.world = World::new(self.editor.tiles.clone(), self.seed);
+            self.world.spawn_enemies(
+                &self.editor.enemy_spawns,
+                &self.editor.patrol_routes,
+                self.editor.random_spawn_fallback,
+            );
+            self.timestep.reset();
+            self.input = InputSnapshot::default();
+        }
+    }
+
+    fn finish_recording(&mut self) {
+        let recording = match self.recording.take() {
+            Some(recording) if !recording.is_empty() => recording,
+            _ => return,
+        };
 
-            self.player.reset();
-            self.player.spawn_player(&self.editor.tiles);
-            spawn_enemy(&self.editor.tiles, &mut self.enemies, self.player.pos(), 5);
+        let path = recording.default_path(&self.replay_dir);
+        match std::fs::create_dir_all(&self.replay_dir).and_then(|_| recording.save(&path)) {
+            Ok(_) => eprintln!("Recorded {} ticks to {}", recording.ticks(), path),
+            Err(err) => eprintln!("{} not saved: {}", path, err),
         }
     }
 
+    fn draw_world(&mut self, frame_time: f32) {
+        let alpha = self.timestep.alpha();
+
+        self.game_camera.target = self
+            .game_camera
+            .target
+            .lerp(self.world.player().render_pos(alpha), damp(0.1, frame_time));
+        self.game_camera.target = self.game_camera.target.round();
+        set_camera(&self.game_camera);
+
+        self.renderer
+            .draw_world(&self.world, alpha, self.debug_collision);
+        self.renderer.draw_hud(&self.world);
+        self.draw_seed();
+    }
+
+    pub fn draw_seed(&self) {
+        root_ui().label(
+            vec2(10.0, screen_height() - 25.0),
+            &format!("Seed: {}", self.seed),
+        );
+    }
+
     pub fn handle_states(&mut self) {
         match self.game_state {
             GameState::EditorMode => {
-                self.editor.editor_camera.update_camera();
+                self.editor.editor_camera.update_camera(&self.input_map);
 
-                draw_map(
-                    &mut self.editor.tiles,
-                    self.level_atlas,
-                    self.debug_collision,
-                );
+                self.editor
+                    .draw_layers(self.renderer.level_atlas(), self.debug_collision);
+                self.editor.draw_tool_preview();
+                self.editor.draw_patrol_routes();
+                self.editor.draw_enemy_spawns();
 
-                if is_key_pressed(KeyCode::F1) {
+                if self.input_map.pressed(Action::EditorModeNone) {
                     self.editor.switch_mode(EditorMode::None);
                 }
-                if is_key_pressed(KeyCode::F2) {
+                if self.input_map.pressed(Action::EditorModePaint) {
                     self.editor.switch_mode(EditorMode::Paint);
                 }
+                if self.input_map.pressed(Action::EditorModePatrol) {
+                    self.editor.switch_mode(EditorMode::Patrol);
+                }
 
-                if is_key_pressed(KeyCode::F4) {
+                if self.input_map.pressed(Action::ToggleCollisionDebug) {
                     self.debug_collision = !self.debug_collision;
                 }
 
                 set_default_camera();
-                self.editor.show_editors();
+                self.editor.show_editors(&self.input_map);
+                self.draw_seed();
             }
             GameState::Play => {
-                if self.player.health() <= 0 {
-                    self.player.set_health(3);
+                if self.world.player_dead() {
+                    self.world.player_mut().set_health(3);
                     self.game_state = GameState::EditorMode;
+                    self.finish_recording();
                 }
 
-                self.game_camera.target = self.game_camera.target.lerp(self.player.pos(), 0.1);
-                self.game_camera.target = self.game_camera.target.round();
-                set_camera(&self.game_camera);
-
-                draw_map(
-                    &mut self.editor.tiles,
-                    self.level_atlas,
-                    self.debug_collision,
-                );
+                self.input.latch(InputSnapshot::read(
+                    self.game_camera,
+                    self.world.player().pos(),
+                    &self.input_map,
+                    self.gamepad.state(),
+                    &mut self.aim_tracker,
+                ));
 
-                for enemy in self.enemies.iter_mut() {
-                    if enemy.pos().distance(self.player.pos()) < 60.0 {
-                        enemy.set_player_spotted(true);
+                let frame_time = get_frame_time();
+                for _ in 0..self.timestep.advance(frame_time) {
+                    self.world.step(&self.input, self.timestep.step());
+                    if let Some(recording) = self.recording.as_mut() {
+                        recording.push(&self.input);
                     }
-
-                    if enemy.player_spotted() {
-                        enemy.move_to(self.player.pos(), &self.editor.tiles);
-                        enemy.damage_player(&mut self.player);
-                    }
-
-                    enemy.draw(self.enemy_sprite_a);
-                    enemy.draw_weapon(self.enemy_sword_sprite, self.player.pos());
+                    self.input.clear_presses();
                 }
 
-                let enemy_list_cloned = self.enemies.clone().to_owned();
-                for (index, enemy) in enemy_list_cloned.iter().enumerate() {
-                    if enemy.health() <= 0 {
-                        self.enemies.remove(index);
+                self.draw_world(frame_time);
+            }
+            GameState::Replay => {
+                let frame_time = get_frame_time();
+                if let Some(replay) = self.replay.as_mut() {
+                    for _ in 0..self.timestep.advance(frame_time) {
+                        match replay.next_input() {
+                            Some(input) => self.world.step(&input, replay.recording().dt()),
+                            None => break,
+                        }
                     }
                 }
 
-                let enemy_count = self.enemies.len();
-                let enemy_list_cloned = self.enemies.clone().to_owned();
-                let mut enemies = self.enemies.iter_mut();
+                self.draw_world(frame_time);
 
-                for i in 0..enemy_count {
-                    if let Some(enemy) = enemies.next() {
-                        for (index, enemy2) in enemy_list_cloned.iter().enumerate() {
-                            if i != index {
-                                enemy.displace(enemy2);
-                            }
-                        }
-                    }
+                if let Some(replay) = self.replay.as_ref() {
+                    let status = if replay.finished() {
+                        String::from("Replay finished")
+                    } else {
+                        format!("Replay {}/{}", replay.tick(), replay.recording().ticks())
+                    };
+                    root_ui().label(vec2(10.0, screen_height() - 45.0), &status);
                 }
-
-                self.player.draw();
-                self.player
-                    .draw_weapon(self.player_sword_sprite, self.game_camera);
-                self.player.attack(&mut self.enemies, self.game_camera);
-                self.player.draw_hearts();
-                self.player.move_player(&self.editor.tiles);
             }
         }
     }
@@ -0,0 +1,636 @@
+pub mod gamepad;
+
+use crate::input::gamepad::*;
+
+use macroquad::prelude::*;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+
+pub const INPUT_CONFIG_PATH: &str = "res/config/input.json";
+
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("Space", KeyCode::Space),
+    ("Apostrophe", KeyCode::Apostrophe),
+    ("Comma", KeyCode::Comma),
+    ("Minus", KeyCode::Minus),
+    ("Period", KeyCode::Period),
+    ("Slash", KeyCode::Slash),
+    ("Key0", KeyCode::Key0),
+    ("Key1", KeyCode::Key1),
+    ("Key2", KeyCode::Key2),
+    ("Key3", KeyCode::Key3),
+    ("Key4", KeyCode::Key4),
+    ("Key5", KeyCode::Key5),
+    ("Key6", KeyCode::Key6),
+    ("Key7", KeyCode::Key7),
+    ("Key8", KeyCode::Key8),
+    ("Key9", KeyCode::Key9),
+    ("Semicolon", KeyCode::Semicolon),
+    ("Equal", KeyCode::Equal),
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("LeftBracket", KeyCode::LeftBracket),
+    ("Backslash", KeyCode::Backslash),
+    ("RightBracket", KeyCode::RightBracket),
+    ("GraveAccent", KeyCode::GraveAccent),
+    ("Escape", KeyCode::Escape),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Insert", KeyCode::Insert),
+    ("Delete", KeyCode::Delete),
+    ("Right", KeyCode::Right),
+    ("Left", KeyCode::Left),
+    ("Down", KeyCode::Down),
+    ("Up", KeyCode::Up),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Kp0", KeyCode::Kp0),
+    ("Kp1", KeyCode::Kp1),
+    ("Kp2", KeyCode::Kp2),
+    ("Kp3", KeyCode::Kp3),
+    ("Kp4", KeyCode::Kp4),
+    ("Kp5", KeyCode::Kp5),
+    ("Kp6", KeyCode::Kp6),
+    ("Kp7", KeyCode::Kp7),
+    ("Kp8", KeyCode::Kp8),
+    ("Kp9", KeyCode::Kp9),
+    ("KpDecimal", KeyCode::KpDecimal),
+    ("KpDivide", KeyCode::KpDivide),
+    ("KpMultiply", KeyCode::KpMultiply),
+    ("KpSubtract", KeyCode::KpSubtract),
+    ("KpAdd", KeyCode::KpAdd),
+    ("KpEnter", KeyCode::KpEnter),
+    ("KpEqual", KeyCode::KpEqual),
+    ("LeftShift", KeyCode::LeftShift),
+    ("LeftControl", KeyCode::LeftControl),
+    ("LeftAlt", KeyCode::LeftAlt),
+    ("RightShift", KeyCode::RightShift),
+    ("RightControl", KeyCode::RightControl),
+    ("RightAlt", KeyCode::RightAlt),
+];
+
+const MOUSE_NAMES: &[(&str, MouseButton)] = &[
+    ("MouseLeft", MouseButton::Left),
+    ("MouseRight", MouseButton::Right),
+    ("MouseMiddle", MouseButton::Middle),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Attack,
+    Block,
+    ToggleEditor,
+    EditorModeNone,
+    EditorModePaint,
+    EditorModePatrol,
+    StopPainting,
+    ToggleCollisionDebug,
+    TogglePaint,
+    ToggleEditWindow,
+    ToggleCollisionPaint,
+    NextTool,
+    Paint,
+    Erase,
+    PlacePlayerSpawn,
+    RemovePlayerSpawn,
+    PlaceEnemySpawn,
+    RemoveEnemySpawn,
+    NewPatrolRoute,
+    AddWaypoint,
+    RemoveWaypoint,
+    Undo,
+    Redo,
+    CameraUp,
+    CameraDown,
+    CameraLeft,
+    CameraRight,
+}
+
+impl Action {
+    pub const ALL: [Action; 31] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Attack,
+        Action::Block,
+        Action::ToggleEditor,
+        Action::EditorModeNone,
+        Action::EditorModePaint,
+        Action::EditorModePatrol,
+        Action::StopPainting,
+        Action::ToggleCollisionDebug,
+        Action::TogglePaint,
+        Action::ToggleEditWindow,
+        Action::ToggleCollisionPaint,
+        Action::NextTool,
+        Action::Paint,
+        Action::Erase,
+        Action::PlacePlayerSpawn,
+        Action::RemovePlayerSpawn,
+        Action::PlaceEnemySpawn,
+        Action::RemoveEnemySpawn,
+        Action::NewPatrolRoute,
+        Action::AddWaypoint,
+        Action::RemoveWaypoint,
+        Action::Undo,
+        Action::Redo,
+        Action::CameraUp,
+        Action::CameraDown,
+        Action::CameraLeft,
+        Action::CameraRight,
+    ];
+
+    fn default_bindings(&self) -> &'static [&'static str] {
+        match self {
+            Action::MoveUp => &["W", "PadDPadUp"],
+            Action::MoveDown => &["S", "PadDPadDown"],
+            Action::MoveLeft => &["A", "PadDPadLeft"],
+            Action::MoveRight => &["D", "PadDPadRight"],
+            Action::Attack => &["MouseLeft", "PadRightTrigger2", "PadWest"],
+            Action::Block => &["MouseRight", "PadLeftTrigger2"],
+            Action::ToggleEditor => &["Ctrl+Tab"],
+            Action::EditorModeNone => &["F1"],
+            Action::EditorModePaint => &["F2"],
+            Action::EditorModePatrol => &["F5"],
+            Action::StopPainting => &["F3"],
+            Action::ToggleCollisionDebug => &["F4"],
+            Action::TogglePaint => &["Ctrl+E"],
+            Action::ToggleEditWindow => &["Ctrl+Q"],
+            Action::ToggleCollisionPaint => &["C"],
+            Action::NextTool => &["T"],
+            Action::Paint => &["MouseLeft"],
+            Action::Erase => &["MouseRight"],
+            Action::PlacePlayerSpawn => &["Ctrl+P"],
+            Action::RemovePlayerSpawn => &["Ctrl+R"],
+            Action::PlaceEnemySpawn => &["Ctrl+O"],
+            Action::RemoveEnemySpawn => &["Ctrl+L"],
+            Action::NewPatrolRoute => &["Ctrl+N"],
+            Action::AddWaypoint => &["MouseLeft"],
+            Action::RemoveWaypoint => &["MouseRight"],
+            Action::Undo => &["Ctrl+Z"],
+            Action::Redo => &["Ctrl+Y"],
+            Action::CameraUp => &["Up"],
+            Action::CameraDown => &["Down"],
+            Action::CameraLeft => &["Left"],
+            Action::CameraRight => &["Right"],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(PadButton),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+    source: InputSource,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+}
+
+impl Binding {
+    pub fn new(source: InputSource) -> Binding {
+        Binding {
+            source,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn with_ctrl(mut self) -> Binding {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn with_shift(mut self) -> Binding {
+        self.shift = true;
+        self
+    }
+
+    pub fn with_alt(mut self) -> Binding {
+        self.alt = true;
+        self
+    }
+
+    pub fn source(&self) -> InputSource {
+        self.source
+    }
+
+    pub fn parse(text: &str) -> Option<Binding> {
+        let mut parts: Vec<&str> = text.split('+').map(|part| part.trim()).collect();
+        let name = parts.pop()?;
+
+        let source = KEY_NAMES
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+            .map(|(_, key)| InputSource::Key(*key))
+            .or_else(|| {
+                MOUSE_NAMES
+                    .iter()
+                    .find(|(mouse_name, _)| mouse_name.eq_ignore_ascii_case(name))
+                    .map(|(_, button)| InputSource::Mouse(*button))
+            })
+            .or_else(|| PadButton::from_name(name).map(InputSource::Gamepad))?;
+
+        let mut binding = Binding::new(source);
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => return None,
+            }
+        }
+
+        Some(binding)
+    }
+
+    pub fn name(&self) -> String {
+        let source = match self.source {
+            InputSource::Key(key) => KEY_NAMES
+                .iter()
+                .find(|(_, named)| *named == key)
+                .map(|(name, _)| *name),
+            InputSource::Mouse(button) => MOUSE_NAMES
+                .iter()
+                .find(|(_, named)| *named == button)
+                .map(|(name, _)| *name),
+            InputSource::Gamepad(button) => Some(button.name()),
+        };
+
+        let mut name = String::new();
+        if self.ctrl {
+            name.push_str("Ctrl+");
+        }
+        if self.shift {
+            name.push_str("Shift+");
+        }
+        if self.alt {
+            name.push_str("Alt+");
+        }
+        name.push_str(source.unwrap_or("Unknown"));
+
+        name
+    }
+
+    pub fn accepts_modifiers(&self, ctrl: bool, shift: bool, alt: bool) -> bool {
+        if !self.ctrl && !self.shift && !self.alt {
+            return !ctrl && !shift && !alt;
+        }
+
+        (!self.ctrl || ctrl) && (!self.shift || shift) && (!self.alt || alt)
+    }
+
+    fn modifiers_down(&self) -> bool {
+        self.accepts_modifiers(
+            is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl),
+            is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift),
+            is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt),
+        )
+    }
+
+    pub fn pressed(&self) -> bool {
+        self.modifiers_down()
+            && match self.source {
+                InputSource::Key(key) => is_key_pressed(key),
+                InputSource::Mouse(button) => is_mouse_button_pressed(button),
+                InputSource::Gamepad(_) => false,
+            }
+    }
+
+    pub fn down(&self) -> bool {
+        self.modifiers_down()
+            && match self.source {
+                InputSource::Key(key) => is_key_down(key),
+                InputSource::Mouse(button) => is_mouse_button_down(button),
+                InputSource::Gamepad(_) => false,
+            }
+    }
+
+    pub fn gamepad_pressed(&self, gamepad: &GamepadState) -> bool {
+        match self.source {
+            InputSource::Gamepad(button) => gamepad.is_pressed(button),
+            _ => false,
+        }
+    }
+
+    pub fn gamepad_down(&self, gamepad: &GamepadState) -> bool {
+        match self.source {
+            InputSource::Gamepad(button) => gamepad.is_down(button),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|action| {
+                let bindings = action
+                    .default_bindings()
+                    .iter()
+                    .filter_map(|text| Binding::parse(text))
+                    .collect();
+
+                (*action, bindings)
+            })
+            .collect();
+
+        InputMap { bindings }
+    }
+}
+
+impl InputMap {
+    pub fn from_json(json: &str) -> serde_json::Result<InputMap> {
+        let config: HashMap<Action, Vec<String>> = serde_json::from_str(json)?;
+        let mut input_map = InputMap::default();
+
+        for (action, names) in config.iter() {
+            let bindings = names
+                .iter()
+                .filter_map(|name| {
+                    let binding = Binding::parse(name);
+                    if binding.is_none() {
+                        eprintln!("Unknown binding {} for {:?}", name, action);
+                    }
+                    binding
+                })
+                .collect();
+
+            input_map.set_bindings(*action, bindings);
+        }
+
+        Ok(input_map)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let config: BTreeMap<Action, Vec<String>> = self
+            .bindings
+            .iter()
+            .map(|(action, bindings)| {
+                (
+                    *action,
+                    bindings.iter().map(|binding| binding.name()).collect(),
+                )
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&config)
+    }
+
+    pub fn load(path: &str) -> InputMap {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                if err.kind() != ErrorKind::NotFound {
+                    eprintln!("{}", err);
+                }
+                return InputMap::default();
+            }
+        };
+
+        InputMap::from_json(&contents).unwrap_or_else(|err| {
+            eprintln!("{} not valid: {}", path, err);
+            InputMap::default()
+        })
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let json = self.to_json()?;
+
+        let mut output = File::create(path)?;
+        write!(output, "{}", json)
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings
+            .get(&action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn set_bindings(&mut self, action: Action, bindings: Vec<Binding>) {
+        self.bindings.insert(action, bindings);
+    }
+
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.pressed())
+    }
+
+    pub fn down(&self, action: Action) -> bool {
+        self.bindings(action).iter().any(|binding| binding.down())
+    }
+
+    pub fn gamepad_pressed(&self, action: Action, gamepad: &GamepadState) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.gamepad_pressed(gamepad))
+    }
+
+    pub fn gamepad_down(&self, action: Action, gamepad: &GamepadState) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.gamepad_down(gamepad))
+    }
+
+    pub fn gamepad_movement(&self, gamepad: &GamepadState) -> Vec2 {
+        let axis = |negative: Action, positive: Action| {
+            let mut value = 0.0;
+            if self.gamepad_down(negative, gamepad) {
+                value -= 1.0;
+            }
+            if self.gamepad_down(positive, gamepad) {
+                value += 1.0;
+            }
+            value
+        };
+
+        let dpad = vec2(
+            axis(Action::MoveLeft, Action::MoveRight),
+            axis(Action::MoveUp, Action::MoveDown),
+        );
+
+        if gamepad.left_stick.length() > dpad.length() {
+            gamepad.left_stick
+        } else {
+            dpad
+        }
+    }
+
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        let mut value = 0.0;
+        if self.down(negative) {
+            value -= 1.0;
+        }
+        if self.down(positive) {
+            value += 1.0;
+        }
+
+        value
+    }
+
+    pub fn movement(&self) -> Vec2 {
+        vec2(
+            self.axis(Action::MoveLeft, Action::MoveRight),
+            self.axis(Action::MoveUp, Action::MoveDown),
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AimTracker {
+    last_mouse: Option<Vec2>,
+    gamepad: bool,
+}
+
+impl AimTracker {
+    pub fn new() -> AimTracker {
+        AimTracker::default()
+    }
+
+    pub fn using_gamepad(&self) -> bool {
+        self.gamepad
+    }
+
+    pub fn aim(&mut self, mouse: Vec2, mouse_world: Vec2, origin: Vec2, right_stick: Vec2) -> Vec2 {
+        let mouse_moved = self
+            .last_mouse
+            .is_none_or(|last| last.distance(mouse) > 0.5);
+        self.last_mouse = Some(mouse);
+
+        if right_stick != Vec2::ZERO {
+            self.gamepad = true;
+            return right_stick.normalize();
+        }
+
+        if mouse_moved {
+            self.gamepad = false;
+        }
+
+        if self.gamepad {
+            Vec2::ZERO
+        } else {
+            (mouse_world - origin).normalize_or_zero()
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct InputSnapshot {
+    pub movement: Vec2,
+    pub aim: Vec2,
+    pub attack: bool,
+    pub block: bool,
+}
+
+impl InputSnapshot {
+    pub fn read(
+        camera: Camera2D,
+        origin: Vec2,
+        input_map: &InputMap,
+        gamepad: &GamepadState,
+        aim_tracker: &mut AimTracker,
+    ) -> InputSnapshot {
+        let mouse = vec2(mouse_position().0, mouse_position().1);
+
+        let keyboard = input_map.movement();
+        let pad = input_map.gamepad_movement(gamepad);
+        let movement = if pad.length() > keyboard.length() {
+            pad
+        } else {
+            keyboard
+        };
+
+        InputSnapshot {
+            movement,
+            aim: aim_tracker.aim(
+                mouse,
+                camera.screen_to_world(mouse),
+                origin,
+                gamepad.right_stick,
+            ),
+            attack: input_map.pressed(Action::Attack)
+                || input_map.gamepad_pressed(Action::Attack, gamepad),
+            block: input_map.pressed(Action::Block)
+                || input_map.gamepad_pressed(Action::Block, gamepad),
+        }
+    }
+
+    pub fn latch(&mut self, frame: InputSnapshot) {
+        self.movement = frame.movement;
+        self.aim = frame.aim;
+        self.attack |= frame.attack;
+        self.block |= frame.block;
+    }
+
+    pub fn clear_presses(&mut self) {
+        self.attack = false;
+        self.block = false;
+    }
+}
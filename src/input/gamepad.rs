@@ -0,0 +1,215 @@
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use macroquad::prelude::*;
+
+pub const STICK_DEADZONE: f32 = 0.2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl PadButton {
+    pub const ALL: [PadButton; 16] = [
+        PadButton::South,
+        PadButton::East,
+        PadButton::North,
+        PadButton::West,
+        PadButton::LeftTrigger,
+        PadButton::LeftTrigger2,
+        PadButton::RightTrigger,
+        PadButton::RightTrigger2,
+        PadButton::Select,
+        PadButton::Start,
+        PadButton::LeftThumb,
+        PadButton::RightThumb,
+        PadButton::DPadUp,
+        PadButton::DPadDown,
+        PadButton::DPadLeft,
+        PadButton::DPadRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PadButton::South => "PadSouth",
+            PadButton::East => "PadEast",
+            PadButton::North => "PadNorth",
+            PadButton::West => "PadWest",
+            PadButton::LeftTrigger => "PadLeftTrigger",
+            PadButton::LeftTrigger2 => "PadLeftTrigger2",
+            PadButton::RightTrigger => "PadRightTrigger",
+            PadButton::RightTrigger2 => "PadRightTrigger2",
+            PadButton::Select => "PadSelect",
+            PadButton::Start => "PadStart",
+            PadButton::LeftThumb => "PadLeftThumb",
+            PadButton::RightThumb => "PadRightThumb",
+            PadButton::DPadUp => "PadDPadUp",
+            PadButton::DPadDown => "PadDPadDown",
+            PadButton::DPadLeft => "PadDPadLeft",
+            PadButton::DPadRight => "PadDPadRight",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PadButton> {
+        PadButton::ALL
+            .iter()
+            .find(|button| button.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    fn button(&self) -> Button {
+        match self {
+            PadButton::South => Button::South,
+            PadButton::East => Button::East,
+            PadButton::North => Button::North,
+            PadButton::West => Button::West,
+            PadButton::LeftTrigger => Button::LeftTrigger,
+            PadButton::LeftTrigger2 => Button::LeftTrigger2,
+            PadButton::RightTrigger => Button::RightTrigger,
+            PadButton::RightTrigger2 => Button::RightTrigger2,
+            PadButton::Select => Button::Select,
+            PadButton::Start => Button::Start,
+            PadButton::LeftThumb => Button::LeftThumb,
+            PadButton::RightThumb => Button::RightThumb,
+            PadButton::DPadUp => Button::DPadUp,
+            PadButton::DPadDown => Button::DPadDown,
+            PadButton::DPadLeft => Button::DPadLeft,
+            PadButton::DPadRight => Button::DPadRight,
+        }
+    }
+
+    fn from_button(button: Button) -> Option<PadButton> {
+        PadButton::ALL
+            .iter()
+            .find(|pad_button| pad_button.button() == button)
+            .copied()
+    }
+}
+
+pub fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+
+    if length <= deadzone {
+        return Vec2::ZERO;
+    }
+
+    let scaled = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+    stick / length * scaled
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    pub connected: bool,
+    pub left_stick: Vec2,
+    pub right_stick: Vec2,
+    pub pressed: Vec<PadButton>,
+    pub down: Vec<PadButton>,
+}
+
+impl GamepadState {
+    pub fn is_pressed(&self, button: PadButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    pub fn is_down(&self, button: PadButton) -> bool {
+        self.down.contains(&button)
+    }
+}
+
+pub struct Gamepad {
+    gilrs: Option<Gilrs>,
+    active: Option<GamepadId>,
+    state: GamepadState,
+}
+
+impl Default for Gamepad {
+    fn default() -> Self {
+        Gamepad::new()
+    }
+}
+
+impl Gamepad {
+    pub fn new() -> Gamepad {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                eprintln!("Gamepad support unavailable: {}", err);
+                None
+            }
+        };
+
+        Gamepad {
+            gilrs,
+            active: None,
+            state: GamepadState::default(),
+        }
+    }
+
+    pub fn state(&self) -> &GamepadState {
+        &self.state
+    }
+
+    pub fn poll(&mut self) -> &GamepadState {
+        self.state.pressed.clear();
+
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return &self.state,
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    self.active = Some(event.id);
+                    if let Some(button) = PadButton::from_button(button) {
+                        self.state.pressed.push(button);
+                    }
+                }
+                EventType::Disconnected if self.active == Some(event.id) => {
+                    self.active = None;
+                }
+                EventType::AxisChanged(..) | EventType::ButtonChanged(..) => {
+                    self.active = Some(event.id);
+                }
+                _ => (),
+            }
+        }
+
+        let gamepad = match self.active.map(|id| gilrs.gamepad(id)) {
+            Some(gamepad) if gamepad.is_connected() => gamepad,
+            _ => {
+                self.state = GamepadState::default();
+                return &self.state;
+            }
+        };
+
+        let stick = |x: Axis, y: Axis| {
+            apply_deadzone(vec2(gamepad.value(x), -gamepad.value(y)), STICK_DEADZONE)
+        };
+
+        self.state.connected = true;
+        self.state.left_stick = stick(Axis::LeftStickX, Axis::LeftStickY);
+        self.state.right_stick = stick(Axis::RightStickX, Axis::RightStickY);
+        self.state.down = PadButton::ALL
+            .iter()
+            .filter(|button| gamepad.is_pressed(button.button()))
+            .copied()
+            .collect();
+
+        &self.state
+    }
+}
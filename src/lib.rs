@@ -5,10 +5,16 @@ extern crate serde_derive;
 
 pub mod editor;
 pub mod enemy;
+pub mod input;
 pub mod player;
+pub mod renderer;
+pub mod replay;
+pub mod rng;
 pub mod serialization;
 pub mod tile;
+pub mod timestep;
 pub mod utility;
+pub mod world;
 
 pub fn setup_window() -> Conf {
     Conf {
@@ -3,11 +3,21 @@ use macroquad::prelude::*;
 pub mod game;
 
 use crate::game::*;
+use dungeon_game::replay::{parse_replay, parse_replay_dir};
+use dungeon_game::rng::parse_seed;
+use dungeon_game::serialization::parse_levels_dir;
 use dungeon_game::setup_window;
 
 #[macroquad::main(setup_window)]
 async fn main() {
-    let mut game = Game::build().await;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut game = Game::build(
+        parse_seed(args.iter().cloned()),
+        parse_replay(args.iter().cloned()),
+        parse_replay_dir(args.iter().cloned()),
+        parse_levels_dir(args.iter().cloned()),
+    )
+    .await;
 
     loop {
         clear_background(LIGHTGRAY);
@@ -0,0 +1,168 @@
+use crate::enemy::*;
+use crate::player::*;
+use crate::utility::*;
+use crate::world::*;
+
+use macroquad::{prelude::*, ui::root_ui};
+
+pub struct Renderer {
+    player_texture: Texture2D,
+    player_sword_texture: Texture2D,
+    enemy_texture: Texture2D,
+    enemy_sword_texture: Texture2D,
+    level_atlas: Texture2D,
+}
+
+impl Renderer {
+    pub fn new(
+        player_texture: Texture2D,
+        player_sword_texture: Texture2D,
+        enemy_texture: Texture2D,
+        enemy_sword_texture: Texture2D,
+        level_atlas: Texture2D,
+    ) -> Renderer {
+        player_texture.set_filter(FilterMode::Nearest);
+
+        Renderer {
+            player_texture,
+            player_sword_texture,
+            enemy_texture,
+            enemy_sword_texture,
+            level_atlas,
+        }
+    }
+
+    pub fn level_atlas(&self) -> Texture2D {
+        self.level_atlas
+    }
+
+    pub fn draw_world(&self, world: &World, alpha: f32, debug_collision: bool) {
+        draw_map(world.tiles(), self.level_atlas, debug_collision);
+
+        let player_pos = world.player().render_pos(alpha);
+        for enemy in world.enemies().iter() {
+            self.draw_enemy(enemy, alpha);
+            self.draw_enemy_weapon(enemy, player_pos, alpha);
+        }
+
+        self.draw_player(world.player(), alpha);
+        self.draw_player_weapon(world.player(), alpha);
+
+        draw_overlay(world.tiles(), self.level_atlas);
+    }
+
+    pub fn draw_hud(&self, world: &World) {
+        let player = world.player();
+
+        root_ui().label(vec2(10.0, 7.0), "Health:");
+
+        if player.health() > 0 {
+            for x in 0..player.health() {
+                root_ui().canvas().rect(
+                    Rect::new(65.0 + (x as f32 * 15.0), 10.0, 10.0, 10.0),
+                    None,
+                    Some(RED),
+                );
+            }
+        }
+
+        if !player.blocking() && player.block_cooldown() > 0.0 {
+            root_ui().label(
+                vec2(10.0, 30.0),
+                format!("Shield in: {} seconds", player.block_cooldown() as i32 + 1).as_str(),
+            );
+        }
+    }
+
+    pub fn draw_player(&self, player: &Player, alpha: f32) {
+        let pos = player.render_pos(alpha);
+        let color = if player.invulnerable() { RED } else { WHITE };
+
+        draw_texture_ex(
+            self.player_texture,
+            pos.x,
+            pos.y,
+            color,
+            DrawTextureParams {
+                dest_size: Some(player.size()),
+                ..Default::default()
+            },
+        );
+
+        if player.blocking() {
+            draw_circle_lines(pos.x + 16.0, pos.y + 16.0, 16.0, 3.0, BLUE);
+        }
+    }
+
+    pub fn draw_player_weapon(&self, player: &Player, alpha: f32) {
+        let sword_pos = player.render_sword_pos(alpha);
+        let flip_y = player.aim().y > 0.0;
+
+        draw_texture_ex(
+            self.player_sword_texture,
+            sword_pos.x,
+            sword_pos.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(32.0, 32.0)),
+                pivot: Some(sword_pos),
+                flip_y,
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn draw_enemy(&self, enemy: &Enemy, alpha: f32) {
+        let color = if enemy.invulnerable() { RED } else { WHITE };
+        let pos = enemy.render_pos(alpha);
+
+        draw_texture_ex(
+            self.enemy_texture,
+            pos.x,
+            pos.y,
+            color,
+            DrawTextureParams {
+                dest_size: Some(vec2(32.0, 32.0)),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn draw_enemy_weapon(&self, enemy: &Enemy, player_pos: Vec2, alpha: f32) {
+        let pos = enemy.render_pos(alpha);
+        let sword_pos = enemy.render_sword_pos(alpha);
+
+        if enemy.player_spotted() {
+            let angle = player_pos.angle_between(sword_pos);
+
+            let flip_y = player_pos.y > pos.y;
+
+            let color = if enemy.weapon_ready() { WHITE } else { RED };
+
+            draw_texture_ex(
+                self.enemy_sword_texture,
+                sword_pos.x,
+                sword_pos.y,
+                color,
+                DrawTextureParams {
+                    dest_size: Some(vec2(32.0, 32.0)),
+                    rotation: angle,
+                    pivot: Some(sword_pos),
+                    flip_y,
+                    ..Default::default()
+                },
+            );
+        } else {
+            draw_texture_ex(
+                self.enemy_sword_texture,
+                pos.x - 12.0,
+                pos.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(32.0, 32.0)),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
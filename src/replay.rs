@@ -0,0 +1,274 @@
+use crate::input::*;
+use crate::serialization::error::*;
+use crate::serialization::*;
+use crate::timestep::*;
+use crate::utility::*;
+use crate::world::*;
+
+use macroquad::prelude::*;
+
+use std::fs::File;
+use std::io::{Error, ErrorKind, Write};
+
+pub const RECORDING_VERSION: u32 = 2;
+
+fn default_levels_dir() -> String {
+    LEVEL_DIR.to_string()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    ticks: u32,
+    movement: [f32; 2],
+    aim: [f32; 2],
+    attack: bool,
+    block: bool,
+}
+
+impl RecordedFrame {
+    fn new(input: &InputSnapshot) -> RecordedFrame {
+        RecordedFrame {
+            ticks: 1,
+            movement: input.movement.to_array(),
+            aim: input.aim.to_array(),
+            attack: input.attack,
+            block: input.block,
+        }
+    }
+
+    fn same_input(&self, other: &RecordedFrame) -> bool {
+        self.movement == other.movement
+            && self.aim == other.aim
+            && self.attack == other.attack
+            && self.block == other.block
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn input(&self) -> InputSnapshot {
+        InputSnapshot {
+            movement: Vec2::from(self.movement),
+            aim: Vec2::from(self.aim),
+            attack: self.attack,
+            block: self.block,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    version: u32,
+    level: String,
+    #[serde(default = "default_levels_dir")]
+    levels_dir: String,
+    level_hash: u64,
+    seed: u64,
+    dt: f32,
+    random_spawn_fallback: bool,
+    frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    pub fn record(
+        levels_dir: &str,
+        level: &str,
+        seed: u64,
+        random_spawn_fallback: bool,
+    ) -> LevelResult<Recording> {
+        let data = load_from(levels_dir, level)?;
+
+        Ok(Recording {
+            version: RECORDING_VERSION,
+            level: level.to_string(),
+            levels_dir: levels_dir.to_string(),
+            level_hash: data.content_hash(),
+            seed,
+            dt: FIXED_DT,
+            random_spawn_fallback,
+            frames: Vec::new(),
+        })
+    }
+
+    pub fn level(&self) -> &str {
+        &self.level
+    }
+
+    pub fn levels_dir(&self) -> &str {
+        &self.levels_dir
+    }
+
+    pub fn level_hash(&self) -> u64 {
+        self.level_hash
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.ticks).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn push(&mut self, input: &InputSnapshot) {
+        let frame = RecordedFrame::new(input);
+
+        match self.frames.last_mut() {
+            Some(last) if last.same_input(&frame) => last.ticks += 1,
+            _ => self.frames.push(frame),
+        }
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = InputSnapshot> + '_ {
+        self.frames
+            .iter()
+            .flat_map(|frame| std::iter::repeat_n(frame.input(), frame.ticks as usize))
+    }
+
+    pub fn build_world(&self) -> std::io::Result<World> {
+        let level = load_from(&self.levels_dir, &self.level).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("level {} could not be loaded: {}", self.level, err),
+            )
+        })?;
+
+        if level.content_hash() != self.level_hash {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("level {} has changed since it was recorded", self.level),
+            ));
+        }
+
+        if level.tiles.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("level {} could not be loaded", self.level),
+            ));
+        }
+
+        let mut world = World::new(level.tiles, self.seed);
+        world.spawn_enemies(
+            &level.enemy_spawns,
+            &level.patrol_routes,
+            self.random_spawn_fallback,
+        );
+
+        Ok(world)
+    }
+
+    pub fn run(&self) -> std::io::Result<World> {
+        let mut world = self.build_world()?;
+
+        for input in self.inputs() {
+            world.step(&input, self.dt);
+        }
+
+        Ok(world)
+    }
+
+    pub fn default_path(&self, dir: &str) -> String {
+        format!("{}/{}_{}.json", dir, level_name(&self.level), self.seed)
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let json = serde_json::to_string(self)?;
+
+        let mut output = File::create(path)?;
+        write!(output, "{}", json)
+    }
+
+    pub fn load(path: &str) -> std::io::Result<Recording> {
+        let contents = std::fs::read_to_string(path)?;
+        let recording: Recording = serde_json::from_str(&contents)?;
+
+        if recording.version != RECORDING_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported recording version {}", recording.version),
+            ));
+        }
+
+        Ok(recording)
+    }
+}
+
+pub struct Replay {
+    recording: Recording,
+    inputs: Vec<InputSnapshot>,
+    tick: usize,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Replay {
+        let inputs = recording.inputs().collect();
+
+        Replay {
+            recording,
+            inputs,
+            tick: 0,
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.inputs.len()
+    }
+
+    pub fn next_input(&mut self) -> Option<InputSnapshot> {
+        let input = self.inputs.get(self.tick).copied();
+        if input.is_some() {
+            self.tick += 1;
+        }
+
+        input
+    }
+
+    pub fn restart(&mut self) {
+        self.tick = 0;
+    }
+}
+
+pub fn parse_replay<I: Iterator<Item = String>>(args: I) -> Option<String> {
+    parse_arg(args, "--replay")
+}
+
+pub fn parse_replay_dir<I: Iterator<Item = String>>(args: I) -> Option<String> {
+    parse_arg(args, "--replay-dir")
+}
+
+pub fn default_replay_dir() -> String {
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".local/share"))
+        })
+        .or_else(|| std::env::var_os("APPDATA").map(std::path::PathBuf::from))
+        .unwrap_or_else(std::env::temp_dir);
+
+    data_dir
+        .join("dungeon_game")
+        .join("replays")
+        .to_string_lossy()
+        .to_string()
+}
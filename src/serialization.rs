@@ -1,13 +1,23 @@
+pub mod compact;
+pub mod directory;
+pub mod error;
+pub mod validation;
+
 use macroquad::prelude::*;
 
+use std::fs::File;
 use std::io::Write;
-use std::{fs::File, io::ErrorKind};
+use std::path::Path;
 
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
-use serde_json::Result as JsonResult;
-
+use crate::enemy::patrol::*;
+use crate::enemy::spawn::*;
+use crate::enemy::*;
+use crate::serialization::compact::*;
+use crate::serialization::error::*;
 use crate::tile::*;
+use crate::utility::*;
 
 impl Serialize for TileType {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -21,16 +31,44 @@ impl Serialize for TileType {
             TileType::Floor(num) => {
                 serializer.serialize_newtype_variant("TileType", 1, "Floor", &num)
             }
-            TileType::Wall(num) => {
-                serializer.serialize_newtype_variant("TileType", 2, "Wall", &num)
-            }
             TileType::PlayerSpawn(num) => {
-                serializer.serialize_newtype_variant("TileType", 3, "PlayerSpawn", &num)
+                serializer.serialize_newtype_variant("TileType", 2, "PlayerSpawn", &num)
             }
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+enum SavedTileType {
+    Empty(i32),
+    Floor(i32),
+    Wall(i32),
+    PlayerSpawn(i32),
+}
+
+impl SavedTileType {
+    fn from_tile_type(tile_type: TileType) -> SavedTileType {
+        match tile_type {
+            TileType::Empty(num) => SavedTileType::Empty(num),
+            TileType::Floor(num) => SavedTileType::Floor(num),
+            TileType::PlayerSpawn(num) => SavedTileType::PlayerSpawn(num),
+        }
+    }
+
+    fn tile_type(self) -> TileType {
+        match self {
+            SavedTileType::Empty(num) => TileType::Empty(num),
+            SavedTileType::Floor(num) => TileType::Floor(num),
+            SavedTileType::Wall(_) => TileType::Floor(1),
+            SavedTileType::PlayerSpawn(num) => TileType::PlayerSpawn(num),
+        }
+    }
+
+    fn is_wall(self) -> bool {
+        matches!(self, SavedTileType::Wall(_))
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct SerVec2 {
     x: f32,
@@ -96,68 +134,589 @@ impl Serialize for Tile {
     }
 }
 
-pub fn save(tiles: &Vec<Tile>, path: &str) -> JsonResult<()> {
-    let j = serde_json::to_string_pretty(tiles)?;
+pub const LEVEL_VERSION: u32 = 5;
+pub const DEFAULT_ATLAS: &str = "res/textures/tilemap_packed.png";
+pub const LEVEL_DIR: &str = "res/levels";
+pub const JSON_EXTENSION: &str = "json";
+pub const BINARY_EXTENSION: &str = "msgpack";
 
-    let extended_path = format!("res/levels/{}.json", path);
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LevelFormat {
+    Json,
+    MessagePack,
+}
 
-    let mut output = File::create(extended_path).unwrap();
-    write!(output, "{}", j).unwrap();
+impl LevelFormat {
+    pub fn from_path(path: &str) -> LevelFormat {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some(BINARY_EXTENSION) => LevelFormat::MessagePack,
+            _ => LevelFormat::Json,
+        }
+    }
 
-    Ok(())
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LevelFormat::Json => JSON_EXTENSION,
+            LevelFormat::MessagePack => BINARY_EXTENSION,
+        }
+    }
 }
 
-#[derive(Deserialize, Debug)]
+pub fn level_path(path: &str) -> String {
+    level_path_in(LEVEL_DIR, path)
+}
+
+pub fn level_path_in(dir: &str, path: &str) -> String {
+    match Path::new(path).extension() {
+        Some(_) => format!("{}/{}", dir, path),
+        None => format!("{}/{}.{}", dir, path, JSON_EXTENSION),
+    }
+}
+
+pub fn parse_levels_dir<I: Iterator<Item = String>>(args: I) -> Option<String> {
+    parse_arg(args, "--levels-dir")
+}
+
+pub fn level_name(path: &str) -> &str {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path)
+}
+
+pub struct LevelData {
+    pub name: String,
+    pub atlas: String,
+    pub tiles: TileMap,
+    pub patrol_routes: Vec<PatrolRoute>,
+    pub enemy_spawns: Vec<EnemySpawn>,
+}
+
+impl LevelData {
+    pub fn new(name: &str, tiles: TileMap) -> LevelData {
+        LevelData {
+            name: name.to_string(),
+            atlas: DEFAULT_ATLAS.to_string(),
+            tiles,
+            patrol_routes: Vec::new(),
+            enemy_spawns: Vec::new(),
+        }
+    }
+
+    pub fn empty() -> LevelData {
+        LevelData::new("", TileMap::new(0, 0))
+    }
+
+    pub fn player_spawn(&self) -> Option<Vec2> {
+        self.tiles
+            .iter()
+            .find(|tile| matches!(tile.tile_type(), TileType::PlayerSpawn(_)))
+            .map(|tile| tile.pos())
+    }
+
+    pub fn content_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+        let walls: Vec<bool> = self
+            .tiles
+            .cells()
+            .map(|(col, row, _)| self.tiles.is_wall(col, row))
+            .collect();
+        let layers: Vec<(Layer, i32, i32, [f32; 4])> = [Layer::Decoration, Layer::Overlay]
+            .iter()
+            .flat_map(|layer| {
+                self.tiles.layer_cells(*layer).map(|(col, row, source)| {
+                    (*layer, col, row, [source.x, source.y, source.w, source.h])
+                })
+            })
+            .collect();
+        let spawns: Vec<(f32, f32, EnemyKind, u32)> = self
+            .enemy_spawns
+            .iter()
+            .map(|spawn| (spawn.pos().x, spawn.pos().y, spawn.kind(), spawn.count()))
+            .collect();
+        let routes: Vec<Vec<(f32, f32)>> = self
+            .patrol_routes
+            .iter()
+            .map(|route| route.waypoints().iter().map(|pos| (pos.x, pos.y)).collect())
+            .collect();
+
+        let content = (
+            (self.tiles.width(), self.tiles.height()),
+            &self.atlas,
+            self.tiles.tiles(),
+            walls,
+            layers,
+            spawns,
+            routes,
+        );
+
+        serde_json::to_vec(&content)
+            .unwrap_or_default()
+            .iter()
+            .fold(FNV_OFFSET, |hash, byte| {
+                (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct SerEnemySpawn {
+    pos: SerVec2,
+    kind: EnemyKind,
+    count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LoadedTile {
     pos: SerVec2,
     source: SerRec,
-    tile_type: TileType,
+    tile_type: SavedTileType,
+    #[serde(default, skip_serializing_if = "is_false")]
+    solid: bool,
 }
 
-pub fn load(path: &str) -> JsonResult<Vec<Tile>> {
-    let extended_path = format!("res/levels/{}.json", path);
+fn is_false(value: &bool) -> bool {
+    !*value
+}
 
-    let contents = match std::fs::read_to_string(extended_path) {
-        Ok(file) => file,
-        Err(err) => match err.kind() {
-            ErrorKind::NotFound => {
-                eprintln!("{} not found", path);
-                return Ok(Vec::new());
-            }
+impl LoadedTile {
+    fn from_tile(tile: &Tile, solid: bool) -> LoadedTile {
+        LoadedTile {
+            pos: SerVec2 {
+                x: tile.pos().x,
+                y: tile.pos().y,
+            },
+            source: SerRec {
+                x: tile.source().x,
+                y: tile.source().y,
+                w: tile.source().w,
+                h: tile.source().h,
+            },
+            tile_type: SavedTileType::from_tile_type(tile.tile_type()),
+            solid,
+        }
+    }
+
+    fn pos(&self) -> Vec2 {
+        vec2(self.pos.x, self.pos.y)
+    }
+
+    fn to_tile(&self) -> Tile {
+        Tile::new(
+            vec2(self.pos.x, self.pos.y),
+            Rect::new(self.source.x, self.source.y, self.source.w, self.source.h),
+            self.tile_type.tile_type(),
+        )
+    }
+
+    fn is_solid(&self) -> bool {
+        self.solid || self.tile_type.is_wall()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+enum Entity {
+    EnemySpawn {
+        pos: SerVec2,
+        kind: EnemyKind,
+        count: u32,
+    },
+    PatrolRoute {
+        waypoints: Vec<SerVec2>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct LayerCell {
+    col: i32,
+    row: i32,
+    source: SerRec,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct SavedLayer {
+    layer: Layer,
+    cells: Vec<LayerCell>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged, from = "SavedTiles")]
+enum LevelTiles {
+    Compact(CompactTiles),
+    Explicit(Vec<LoadedTile>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedTiles {
+    Compact(CompactTiles),
+    Packed(PackedTiles),
+    Explicit(Vec<LoadedTile>),
+}
+
+impl From<SavedTiles> for LevelTiles {
+    fn from(tiles: SavedTiles) -> LevelTiles {
+        match tiles {
+            SavedTiles::Compact(compact) => LevelTiles::Compact(compact),
+            SavedTiles::Packed(packed) => LevelTiles::Compact(packed.migrate()),
+            SavedTiles::Explicit(explicit) => LevelTiles::Explicit(explicit),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Level {
+    version: u32,
+    name: String,
+    width: i32,
+    height: i32,
+    tile_size: f32,
+    atlas: String,
+    player_spawn: Option<SerVec2>,
+    tiles: LevelTiles,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    layers: Vec<SavedLayer>,
+    #[serde(default)]
+    entities: Vec<Entity>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LevelV1 {
+    tiles: Vec<LoadedTile>,
+    #[serde(default)]
+    patrol_routes: Vec<Vec<SerVec2>>,
+    #[serde(default)]
+    enemy_spawns: Vec<SerEnemySpawn>,
+}
+
+impl Level {
+    pub fn from_data(level: &LevelData) -> Level {
+        let spawns = level.enemy_spawns.iter().map(|spawn| Entity::EnemySpawn {
+            pos: SerVec2 {
+                x: spawn.pos().x,
+                y: spawn.pos().y,
+            },
+            kind: spawn.kind(),
+            count: spawn.count(),
+        });
 
+        let routes = level.patrol_routes.iter().map(|route| Entity::PatrolRoute {
+            waypoints: route
+                .waypoints()
+                .iter()
+                .map(|waypoint| SerVec2 {
+                    x: waypoint.x,
+                    y: waypoint.y,
+                })
+                .collect(),
+        });
+
+        let layers = [Layer::Decoration, Layer::Overlay]
+            .iter()
+            .map(|layer| SavedLayer {
+                layer: *layer,
+                cells: level
+                    .tiles
+                    .layer_cells(*layer)
+                    .map(|(col, row, source)| LayerCell {
+                        col,
+                        row,
+                        source: SerRec {
+                            x: source.x,
+                            y: source.y,
+                            w: source.w,
+                            h: source.h,
+                        },
+                    })
+                    .collect(),
+            })
+            .filter(|layer| !layer.cells.is_empty());
+
+        Level {
+            version: LEVEL_VERSION,
+            name: level.name.clone(),
+            width: level.tiles.width(),
+            height: level.tiles.height(),
+            tile_size: TILE_SIZE,
+            atlas: level.atlas.clone(),
+            player_spawn: level
+                .player_spawn()
+                .map(|pos| SerVec2 { x: pos.x, y: pos.y }),
+            tiles: match CompactTiles::encode(&level.tiles) {
+                Some(compact) => LevelTiles::Compact(compact),
+                None => LevelTiles::Explicit(
+                    level
+                        .tiles
+                        .cells()
+                        .map(|(col, row, tile)| {
+                            LoadedTile::from_tile(tile, level.tiles.is_wall(col, row))
+                        })
+                        .collect(),
+                ),
+            },
+            layers: layers.collect(),
+            entities: spawns.chain(routes).collect(),
+        }
+    }
+
+    pub fn parse(json: &str, name: &str) -> LevelResult<Level> {
+        Level::from_value(serde_json::from_str(json)?, name)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> LevelResult<Level> {
+        let level: Level = rmp_serde::from_slice(bytes)?;
+        level.check()
+    }
+
+    pub fn to_bytes(&self) -> LevelResult<Vec<u8>> {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
+
+    fn from_value(value: serde_json::Value, name: &str) -> LevelResult<Level> {
+        let version = match &value {
+            serde_json::Value::Array(_) => 0,
+            serde_json::Value::Object(fields) => match fields.get("version") {
+                Some(version) => version
+                    .as_u64()
+                    .ok_or_else(|| LevelError::Parse("level version is not a number".to_string()))?
+                    as u32,
+                None => 1,
+            },
             _ => {
-                eprintln!("{}", err.to_string());
-                return Ok(Vec::new());
+                return Err(LevelError::Parse(
+                    "level is not an array or object".to_string(),
+                ))
             }
-        },
-    };
+        };
+
+        if version > LEVEL_VERSION {
+            return Err(LevelError::UnsupportedVersion(version));
+        }
+
+        let level: Level = match version {
+            0 => Level::migrate_v1(
+                LevelV1 {
+                    tiles: serde_json::from_value(value)?,
+                    patrol_routes: Vec::new(),
+                    enemy_spawns: Vec::new(),
+                },
+                name,
+            ),
+            1 => Level::migrate_v1(serde_json::from_value(value)?, name),
+            _ => serde_json::from_value(value)?,
+        };
+
+        level.check()
+    }
+
+    fn check(self) -> LevelResult<Level> {
+        if self.version > LEVEL_VERSION {
+            return Err(LevelError::UnsupportedVersion(self.version));
+        }
+
+        if self.tile_size != TILE_SIZE {
+            return Err(LevelError::Validation(format!(
+                "tile size {} does not match the game's tile size {}",
+                self.tile_size, TILE_SIZE
+            )));
+        }
+
+        if self.width <= 0
+            || self.height <= 0
+            || self.width > MAX_MAP_SIZE
+            || self.height > MAX_MAP_SIZE
+        {
+            return Err(LevelError::Validation(format!(
+                "{}x{} map is outside the supported 1x1 to {}x{} range",
+                self.width, self.height, MAX_MAP_SIZE, MAX_MAP_SIZE
+            )));
+        }
 
-    let tiles: Vec<LoadedTile> = match serde_json::from_str(&contents) {
-        Ok(data) => data,
-        Err(err) => {
-            if err.is_data() {
-                eprintln!("{} data not valid", path);
-            } else if err.is_io() {
-                eprintln!("Could not read {}", path);
-            } else if err.is_syntax() {
-                eprintln!("{} syntax not valid", path);
+        match &self.tiles {
+            LevelTiles::Compact(compact) => compact
+                .validate(self.width, self.height)
+                .map_err(LevelError::Validation)?,
+            LevelTiles::Explicit(explicit) => {
+                for tile in explicit.iter() {
+                    let (col, row) = world_to_grid(tile.pos());
+                    if col < 0 || row < 0 || col >= self.width || row >= self.height {
+                        return Err(LevelError::Validation(format!(
+                            "tile ({}, {}) is outside the {}x{} map",
+                            col, row, self.width, self.height
+                        )));
+                    }
+                }
             }
+        }
 
-            eprintln!("{}", err.to_string());
+        for saved in self.layers.iter() {
+            if !matches!(saved.layer, Layer::Decoration | Layer::Overlay) {
+                return Err(LevelError::Validation(format!(
+                    "{} layer can't be stored separately from the tiles",
+                    saved.layer.name()
+                )));
+            }
 
-            Vec::new()
+            for cell in saved.cells.iter() {
+                if cell.col < 0 || cell.row < 0 || cell.col >= self.width || cell.row >= self.height
+                {
+                    return Err(LevelError::Validation(format!(
+                        "{} layer cell ({}, {}) is outside the {}x{} map",
+                        saved.layer.name(),
+                        cell.col,
+                        cell.row,
+                        self.width,
+                        self.height
+                    )));
+                }
+            }
         }
-    };
 
-    let mut converted_tiles = Vec::new();
+        Ok(self)
+    }
+
+    fn migrate_v1(level: LevelV1, name: &str) -> Level {
+        let (width, height) = grid_extent(level.tiles.iter().map(LoadedTile::pos));
+
+        let spawns = level
+            .enemy_spawns
+            .into_iter()
+            .map(|spawn| Entity::EnemySpawn {
+                pos: spawn.pos,
+                kind: spawn.kind,
+                count: spawn.count,
+            });
+
+        let routes = level
+            .patrol_routes
+            .into_iter()
+            .map(|waypoints| Entity::PatrolRoute { waypoints });
+
+        Level {
+            version: LEVEL_VERSION,
+            name: name.to_string(),
+            width,
+            height,
+            tile_size: TILE_SIZE,
+            atlas: DEFAULT_ATLAS.to_string(),
+            player_spawn: None,
+            tiles: LevelTiles::Explicit(level.tiles),
+            layers: Vec::new(),
+            entities: spawns.chain(routes).collect(),
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 
-    for tile in tiles.iter() {
-        converted_tiles.push(Tile::new(
-            vec2(tile.pos.x, tile.pos.y),
-            Rect::new(tile.source.x, tile.source.y, tile.source.w, tile.source.h),
-            tile.tile_type,
-        ));
+    pub fn width(&self) -> i32 {
+        self.width
     }
 
-    Ok(converted_tiles)
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn tile_size(&self) -> f32 {
+        self.tile_size
+    }
+
+    pub fn atlas(&self) -> &str {
+        &self.atlas
+    }
+
+    pub fn compact_tiles(&self) -> Option<&CompactTiles> {
+        match &self.tiles {
+            LevelTiles::Compact(compact) => Some(compact),
+            LevelTiles::Explicit(_) => None,
+        }
+    }
+
+    pub fn into_data(self) -> LevelData {
+        let mut tiles = match &self.tiles {
+            LevelTiles::Compact(compact) => compact.decode(self.width, self.height),
+            LevelTiles::Explicit(_) => TileMap::new(self.width, self.height),
+        };
+        if let LevelTiles::Explicit(explicit) = &self.tiles {
+            for tile in explicit.iter() {
+                let (col, row) = world_to_grid(vec2(tile.pos.x, tile.pos.y));
+                tiles.set_legacy_tile(col, row, tile.to_tile());
+                if tile.is_solid() {
+                    tiles.set_wall(col, row, true);
+                }
+            }
+        }
+
+        let has_spawn_tile = tiles
+            .iter()
+            .any(|tile| matches!(tile.tile_type(), TileType::PlayerSpawn(_)));
+        if let (false, Some(spawn)) = (has_spawn_tile, &self.player_spawn) {
+            let (col, row) = tiles.world_to_grid(vec2(spawn.x, spawn.y));
+            tiles.set_type(col, row, TileType::PlayerSpawn(3));
+        }
+
+        for saved in self.layers.iter() {
+            for cell in saved.cells.iter() {
+                let source = Rect::new(cell.source.x, cell.source.y, cell.source.w, cell.source.h);
+                tiles.set_layer_source(saved.layer, cell.col, cell.row, source);
+            }
+        }
+
+        let mut level = LevelData::new(&self.name, tiles);
+        level.atlas = self.atlas;
+
+        for entity in self.entities.into_iter() {
+            match entity {
+                Entity::EnemySpawn { pos, kind, count } => level
+                    .enemy_spawns
+                    .push(EnemySpawn::new(vec2(pos.x, pos.y), kind, count)),
+                Entity::PatrolRoute { waypoints } => level.patrol_routes.push(PatrolRoute::new(
+                    waypoints.iter().map(|pos| vec2(pos.x, pos.y)).collect(),
+                )),
+            }
+        }
+
+        level
+    }
+}
+
+pub fn save(level: &LevelData, path: &str) -> LevelResult<()> {
+    save_to(LEVEL_DIR, level, path)
+}
+
+pub fn save_to(dir: &str, level: &LevelData, path: &str) -> LevelResult<()> {
+    let level = Level::from_data(level);
+
+    let contents = match LevelFormat::from_path(path) {
+        LevelFormat::Json => serde_json::to_string_pretty(&level)?.into_bytes(),
+        LevelFormat::MessagePack => level.to_bytes()?,
+    };
+
+    let mut output = File::create(level_path_in(dir, path))?;
+    output.write_all(&contents)?;
+
+    Ok(())
+}
+
+pub fn load(path: &str) -> LevelResult<LevelData> {
+    load_from(LEVEL_DIR, path)
+}
+
+pub fn load_from(dir: &str, path: &str) -> LevelResult<LevelData> {
+    let contents = std::fs::read(level_path_in(dir, path))?;
+
+    let level = match LevelFormat::from_path(path) {
+        LevelFormat::Json => Level::parse(std::str::from_utf8(&contents)?, level_name(path))?,
+        LevelFormat::MessagePack => Level::from_bytes(&contents)?,
+    };
+
+    Ok(level.into_data())
 }
@@ -0,0 +1,287 @@
+use crate::tile::*;
+
+use macroquad::prelude::*;
+
+pub const DEFAULT_ATLAS_TILE_SIZE: f32 = 16.0;
+pub const DEFAULT_ATLAS_COLUMNS: i32 = 12;
+pub const EMPTY_INDEX: i32 = -1;
+pub const EMPTY_CODE: i32 = 0;
+pub const FLOOR_CODE: i32 = 1;
+pub const WALL_CODE: i32 = 2;
+pub const SPAWN_CODE: i32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GridLayer {
+    Raw(Vec<i32>),
+    Rle(Vec<(i32, u32)>),
+}
+
+impl GridLayer {
+    pub fn raw(values: &[i32]) -> GridLayer {
+        GridLayer::Raw(values.to_vec())
+    }
+
+    pub fn rle(values: &[i32]) -> GridLayer {
+        let mut runs: Vec<(i32, u32)> = Vec::new();
+
+        for &value in values.iter() {
+            match runs.last_mut() {
+                Some((last, count)) if *last == value => *count += 1,
+                _ => runs.push((value, 1)),
+            }
+        }
+
+        GridLayer::Rle(runs)
+    }
+
+    pub fn encode(values: &[i32]) -> GridLayer {
+        let rle = GridLayer::rle(values);
+
+        match &rle {
+            GridLayer::Rle(runs) if runs.len() * 2 < values.len() => rle,
+            _ => GridLayer::raw(values),
+        }
+    }
+
+    pub fn decode(&self) -> Vec<i32> {
+        match self {
+            GridLayer::Raw(values) => values.clone(),
+            GridLayer::Rle(runs) => runs
+                .iter()
+                .flat_map(|&(value, count)| std::iter::repeat_n(value, count as usize))
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            GridLayer::Raw(values) => values.len(),
+            GridLayer::Rle(runs) => runs.iter().map(|&(_, count)| count as usize).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_rle(&self) -> bool {
+        matches!(self, GridLayer::Rle(_))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompactTiles {
+    atlas_tile_size: f32,
+    atlas_columns: i32,
+    atlas: GridLayer,
+    types: GridLayer,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payloads: Option<GridLayer>,
+    solid: GridLayer,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PackedTiles {
+    atlas_tile_size: f32,
+    atlas_columns: i32,
+    atlas: GridLayer,
+    collision: GridLayer,
+}
+
+impl PackedTiles {
+    pub fn migrate(self) -> CompactTiles {
+        let collision = self.collision.decode();
+        let types = collision
+            .iter()
+            .map(|&code| if code == WALL_CODE { FLOOR_CODE } else { code })
+            .collect::<Vec<i32>>();
+        let solid = collision
+            .iter()
+            .map(|&code| (code == WALL_CODE) as i32)
+            .collect::<Vec<i32>>();
+
+        CompactTiles {
+            atlas_tile_size: self.atlas_tile_size,
+            atlas_columns: self.atlas_columns,
+            atlas: self.atlas,
+            types: GridLayer::encode(&types),
+            payloads: None,
+            solid: GridLayer::encode(&solid),
+        }
+    }
+}
+
+impl CompactTiles {
+    pub fn encode(tiles: &TileMap) -> Option<CompactTiles> {
+        let atlas_tile_size = tiles
+            .iter()
+            .map(|tile| tile.source())
+            .find(|source| !is_empty_source(*source))
+            .map(|source| source.w)
+            .unwrap_or(DEFAULT_ATLAS_TILE_SIZE);
+
+        if atlas_tile_size <= 0.0 {
+            return None;
+        }
+
+        let mut cells = Vec::with_capacity(tiles.len());
+        let mut types = Vec::with_capacity(tiles.len());
+        let mut payloads = Vec::with_capacity(tiles.len());
+        let mut solid = Vec::with_capacity(tiles.len());
+        let mut atlas_columns = DEFAULT_ATLAS_COLUMNS;
+
+        for (col, row, tile) in tiles.cells() {
+            if tile.pos() != grid_to_world(col, row) {
+                return None;
+            }
+
+            let (code, payload) = type_code(tile.tile_type());
+            types.push(code);
+            payloads.push(payload);
+            solid.push(tiles.is_wall(col, row) as i32);
+
+            let cell = atlas_cell(tile.source(), atlas_tile_size)?;
+            if let Some((atlas_col, _)) = cell {
+                atlas_columns = atlas_columns.max(atlas_col + 1);
+            }
+            cells.push(cell);
+        }
+
+        let atlas = cells
+            .iter()
+            .map(|cell| match cell {
+                Some((atlas_col, atlas_row)) => atlas_row * atlas_columns + atlas_col,
+                None => EMPTY_INDEX,
+            })
+            .collect::<Vec<i32>>();
+
+        Some(CompactTiles {
+            atlas_tile_size,
+            atlas_columns,
+            atlas: GridLayer::encode(&atlas),
+            types: GridLayer::encode(&types),
+            payloads: (payloads != types).then(|| GridLayer::encode(&payloads)),
+            solid: GridLayer::encode(&solid),
+        })
+    }
+
+    pub fn decode(&self, width: i32, height: i32) -> TileMap {
+        let mut tiles = TileMap::new(width, height);
+        let atlas = self.atlas.decode();
+        let types = self.types.decode();
+        let payloads = self.payloads.as_ref().map(GridLayer::decode);
+        let solid = self.solid.decode();
+        let columns = self.atlas_columns.max(1);
+
+        for index in 0..tiles.len() {
+            let (col, row) = (index as i32 % width, index as i32 / width);
+
+            if let Some(&code) = types.get(index) {
+                let payload = payloads
+                    .as_ref()
+                    .and_then(|payloads| payloads.get(index).copied())
+                    .unwrap_or(code);
+                tiles.set_type(col, row, tile_type(code, payload));
+            }
+
+            if let Some(&cell) = atlas.get(index).filter(|&&cell| cell >= 0) {
+                let source = Rect::new(
+                    (cell % columns) as f32 * self.atlas_tile_size,
+                    (cell / columns) as f32 * self.atlas_tile_size,
+                    self.atlas_tile_size,
+                    self.atlas_tile_size,
+                );
+                tiles.set_layer_source(Layer::Ground, col, row, source);
+            }
+
+            if solid.get(index).is_some_and(|&bit| bit != 0) {
+                tiles.set_wall(col, row, true);
+            }
+        }
+
+        tiles
+    }
+
+    pub fn validate(&self, width: i32, height: i32) -> Result<(), String> {
+        let cells = cell_count(width, height)
+            .ok_or_else(|| format!("{}x{} map has too many cells", width, height))?;
+
+        let layers = [
+            ("atlas", Some(&self.atlas)),
+            ("types", Some(&self.types)),
+            ("payloads", self.payloads.as_ref()),
+            ("solid", Some(&self.solid)),
+        ];
+
+        for (name, layer) in layers {
+            if let Some(layer) = layer.filter(|layer| layer.len() != cells) {
+                return Err(format!(
+                    "{} layer has {} cells, expected {}",
+                    name,
+                    layer.len(),
+                    cells
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn atlas_tile_size(&self) -> f32 {
+        self.atlas_tile_size
+    }
+
+    pub fn atlas_columns(&self) -> i32 {
+        self.atlas_columns
+    }
+
+    pub fn atlas(&self) -> &GridLayer {
+        &self.atlas
+    }
+
+    pub fn types(&self) -> &GridLayer {
+        &self.types
+    }
+
+    pub fn payloads(&self) -> Option<&GridLayer> {
+        self.payloads.as_ref()
+    }
+
+    pub fn solid(&self) -> &GridLayer {
+        &self.solid
+    }
+}
+
+fn atlas_cell(source: Rect, atlas_tile_size: f32) -> Option<Option<(i32, i32)>> {
+    if is_empty_source(source) {
+        return Some(None);
+    }
+
+    if source.w != atlas_tile_size || source.h != atlas_tile_size {
+        return None;
+    }
+
+    let col = source.x / atlas_tile_size;
+    let row = source.y / atlas_tile_size;
+    if col < 0.0 || row < 0.0 || col.fract() != 0.0 || row.fract() != 0.0 {
+        return None;
+    }
+
+    Some(Some((col as i32, row as i32)))
+}
+
+fn type_code(tile_type: TileType) -> (i32, i32) {
+    match tile_type {
+        TileType::Empty(payload) => (EMPTY_CODE, payload),
+        TileType::Floor(payload) => (FLOOR_CODE, payload),
+        TileType::PlayerSpawn(payload) => (SPAWN_CODE, payload),
+    }
+}
+
+fn tile_type(code: i32, payload: i32) -> TileType {
+    match code {
+        FLOOR_CODE => TileType::Floor(payload),
+        SPAWN_CODE => TileType::PlayerSpawn(payload),
+        _ => TileType::Empty(payload),
+    }
+}
@@ -0,0 +1,101 @@
+use crate::serialization::error::*;
+use crate::serialization::*;
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelEntry {
+    name: String,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl LevelEntry {
+    pub fn new(name: &str, size: u64, modified: Option<SystemTime>) -> LevelEntry {
+        LevelEntry {
+            name: name.to_string(),
+            size,
+            modified,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    pub fn size_label(&self) -> String {
+        if self.size < 1024 {
+            format!("{} B", self.size)
+        } else if self.size < 1024 * 1024 {
+            format!("{:.1} KB", self.size as f64 / 1024.0)
+        } else {
+            format!("{:.1} MB", self.size as f64 / (1024.0 * 1024.0))
+        }
+    }
+
+    pub fn modified_label(&self, now: SystemTime) -> String {
+        let age = match self.modified {
+            Some(modified) => now.duration_since(modified).unwrap_or(Duration::ZERO),
+            None => return "unknown".to_string(),
+        };
+
+        let seconds = age.as_secs();
+        if seconds < 60 {
+            "just now".to_string()
+        } else if seconds < 60 * 60 {
+            format!("{}m ago", seconds / 60)
+        } else if seconds < 60 * 60 * 24 {
+            format!("{}h ago", seconds / (60 * 60))
+        } else {
+            format!("{}d ago", seconds / (60 * 60 * 24))
+        }
+    }
+}
+
+pub fn entry_name(file_name: &str) -> Option<String> {
+    let path = Path::new(file_name);
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(JSON_EXTENSION) => Some(level_name(file_name).to_string()),
+        Some(BINARY_EXTENSION) => Some(file_name.to_string()),
+        _ => None,
+    }
+}
+
+pub fn list_levels(dir: &str) -> LevelResult<Vec<LevelEntry>> {
+    let mut entries = Vec::new();
+
+    for file in std::fs::read_dir(dir)? {
+        let file = file?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+
+        let file_name = file.file_name();
+        if let Some(name) = file_name.to_str().and_then(entry_name) {
+            entries.push(LevelEntry::new(
+                &name,
+                metadata.len(),
+                metadata.modified().ok(),
+            ));
+        }
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(entries)
+}
+
+pub fn level_exists(dir: &str, path: &str) -> bool {
+    Path::new(&level_path_in(dir, path)).is_file()
+}
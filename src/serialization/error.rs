@@ -0,0 +1,66 @@
+use std::fmt;
+
+pub type LevelResult<T> = Result<T, LevelError>;
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Parse(String),
+    Validation(String),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "io error: {}", err),
+            LevelError::Parse(msg) => write!(f, "parse error: {}", msg),
+            LevelError::Validation(msg) => write!(f, "invalid level: {}", msg),
+            LevelError::UnsupportedVersion(version) => write!(
+                f,
+                "level version {} is newer than supported version {}",
+                version,
+                crate::serialization::LEVEL_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LevelError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LevelError {
+    fn from(err: std::io::Error) -> LevelError {
+        LevelError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LevelError {
+    fn from(err: serde_json::Error) -> LevelError {
+        LevelError::Parse(err.to_string())
+    }
+}
+
+impl From<rmp_serde::decode::Error> for LevelError {
+    fn from(err: rmp_serde::decode::Error) -> LevelError {
+        LevelError::Parse(err.to_string())
+    }
+}
+
+impl From<rmp_serde::encode::Error> for LevelError {
+    fn from(err: rmp_serde::encode::Error) -> LevelError {
+        LevelError::Parse(err.to_string())
+    }
+}
+
+impl From<std::str::Utf8Error> for LevelError {
+    fn from(err: std::str::Utf8Error) -> LevelError {
+        LevelError::Parse(err.to_string())
+    }
+}
@@ -0,0 +1,209 @@
+use crate::enemy::navigation::*;
+use crate::tile::*;
+
+use macroquad::prelude::*;
+
+use std::collections::VecDeque;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Diagnostic {
+    NoPlayerSpawn,
+    MultiplePlayerSpawns(Vec<(i32, i32)>),
+    SolidPlayerSpawn((i32, i32)),
+    UnreachableFloor {
+        cells: usize,
+        first: (i32, i32),
+    },
+    InvalidAtlasRect {
+        layer: Layer,
+        cell: (i32, i32),
+        source: Rect,
+    },
+    OffGridPosition {
+        cell: (i32, i32),
+        pos: Vec2,
+    },
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Diagnostic::UnreachableFloor { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Diagnostic::NoPlayerSpawn => "Level has no player spawn".to_string(),
+            Diagnostic::MultiplePlayerSpawns(cells) => format!(
+                "Level has {} player spawns: {}",
+                cells.len(),
+                cells
+                    .iter()
+                    .map(|(col, row)| format!("({}, {})", col, row))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Diagnostic::SolidPlayerSpawn(cell) => {
+                format!("Player spawn ({}, {}) is on a solid cell", cell.0, cell.1)
+            }
+            Diagnostic::UnreachableFloor { cells, first } => format!(
+                "{} floor tiles near ({}, {}) can't be reached from the spawn",
+                cells, first.0, first.1
+            ),
+            Diagnostic::InvalidAtlasRect {
+                layer,
+                cell,
+                source,
+            } => format!(
+                "{} tile ({}, {}) samples {}x{} at ({}, {}) outside the atlas",
+                layer.name(),
+                cell.0,
+                cell.1,
+                source.w,
+                source.h,
+                source.x,
+                source.y
+            ),
+            Diagnostic::OffGridPosition { cell, pos } => format!(
+                "Tile ({}, {}) sits at ({}, {}) instead of its grid position",
+                cell.0, cell.1, pos.x, pos.y
+            ),
+        }
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity() == Severity::Error)
+}
+
+pub fn validate_level(tiles: &TileMap, atlas_size: Vec2) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let spawns: Vec<(i32, i32)> = tiles
+        .cells()
+        .filter(|(_, _, tile)| matches!(tile.tile_type(), TileType::PlayerSpawn(_)))
+        .map(|(col, row, _)| (col, row))
+        .collect();
+
+    match spawns.len() {
+        0 => diagnostics.push(Diagnostic::NoPlayerSpawn),
+        1 => {}
+        _ => diagnostics.push(Diagnostic::MultiplePlayerSpawns(spawns.clone())),
+    }
+
+    for &(col, row) in spawns.iter() {
+        if tiles.is_wall(col, row) {
+            diagnostics.push(Diagnostic::SolidPlayerSpawn((col, row)));
+        }
+    }
+
+    for (col, row, tile) in tiles.cells() {
+        if tile.pos() != grid_to_world(col, row) {
+            diagnostics.push(Diagnostic::OffGridPosition {
+                cell: (col, row),
+                pos: tile.pos(),
+            });
+        }
+
+        if !atlas_rect_valid(tile.source(), atlas_size) {
+            diagnostics.push(Diagnostic::InvalidAtlasRect {
+                layer: Layer::Ground,
+                cell: (col, row),
+                source: tile.source(),
+            });
+        }
+    }
+
+    for layer in [Layer::Decoration, Layer::Overlay] {
+        for (col, row, source) in tiles.layer_cells(layer) {
+            if !atlas_rect_valid(source, atlas_size) {
+                diagnostics.push(Diagnostic::InvalidAtlasRect {
+                    layer,
+                    cell: (col, row),
+                    source,
+                });
+            }
+        }
+    }
+
+    if let Some(&spawn) = spawns.first() {
+        diagnostics.extend(unreachable_floors(tiles, spawn));
+    }
+
+    diagnostics
+}
+
+fn atlas_rect_valid(source: Rect, atlas_size: Vec2) -> bool {
+    if source.w == 0.0 && source.h == 0.0 {
+        return true;
+    }
+
+    source.w > 0.0
+        && source.h > 0.0
+        && source.x >= 0.0
+        && source.y >= 0.0
+        && source.x + source.w <= atlas_size.x
+        && source.y + source.h <= atlas_size.y
+}
+
+fn flood(nav_grid: &NavGrid, start: (i32, i32), visited: &mut [bool]) -> Vec<(i32, i32)> {
+    let mut region = Vec::new();
+    let mut open = VecDeque::new();
+
+    if let Some(index) = nav_grid.index(start.0, start.1) {
+        visited[index] = true;
+        open.push_back(start);
+    }
+
+    while let Some((col, row)) = open.pop_front() {
+        region.push((col, row));
+
+        for (x, y) in nav_grid.neighbors(col, row, NeighborMode::Four) {
+            if let Some(index) = nav_grid.index(x, y) {
+                if !visited[index] {
+                    visited[index] = true;
+                    open.push_back((x, y));
+                }
+            }
+        }
+    }
+
+    region
+}
+
+fn unreachable_floors(tiles: &TileMap, spawn: (i32, i32)) -> Vec<Diagnostic> {
+    let nav_grid = NavGrid::from_tile_map(tiles);
+    let mut visited = vec![false; tiles.len()];
+    flood(&nav_grid, spawn, &mut visited);
+
+    let mut diagnostics = Vec::new();
+    for (col, row, _) in tiles.cells() {
+        let index = (row * tiles.width() + col) as usize;
+        if visited[index] || !tiles.is_floor(col, row) {
+            continue;
+        }
+
+        let region = flood(&nav_grid, (col, row), &mut visited);
+        let cells = region
+            .iter()
+            .filter(|(x, y)| tiles.is_floor(*x, *y))
+            .count();
+
+        diagnostics.push(Diagnostic::UnreachableFloor {
+            cells,
+            first: (col, row),
+        });
+    }
+
+    diagnostics
+}
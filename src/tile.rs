@@ -1,13 +1,13 @@
 use macroquad::prelude::*;
 
-#[derive(Deserialize, Debug, Copy, Clone)]
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
 pub enum TileType {
     Empty(i32),
     Floor(i32),
-    Wall(i32),
     PlayerSpawn(i32),
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Tile {
     source: Rect,
     pos: Vec2,
@@ -26,7 +26,7 @@ impl Tile {
     pub fn empty(pos: Vec2) -> Tile {
         Tile {
             pos,
-            source: Rect::new(0.0, 0.0, 0.0, 0.0),
+            source: empty_source(),
             tile_type: TileType::Empty(0),
         }
     }
@@ -57,24 +57,395 @@ impl Tile {
     }
 }
 
-pub fn get_tile(x: f32, y: f32, tiles: &Vec<Tile>) -> bool {
-    let result_x = ((x + 15.0) as i32 / 32) * 32;
-    let result_y = ((y + 15.0) as i32 / 32) * 32;
+pub const TILE_SIZE: f32 = 32.0;
+pub const MAX_MAP_SIZE: i32 = 256;
 
-    for tile in tiles.iter() {
-        if tile.pos() == vec2(result_x as f32, result_y as f32) {
-            if let TileType::Wall(_) = tile.tile_type() {
-                return true;
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Layer {
+    Ground,
+    Decoration,
+    Collision,
+    Overlay,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 4] = [
+        Layer::Ground,
+        Layer::Decoration,
+        Layer::Collision,
+        Layer::Overlay,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layer::Ground => "Ground",
+            Layer::Decoration => "Decoration",
+            Layer::Collision => "Collision",
+            Layer::Overlay => "Overlay",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            Layer::Ground => 0,
+            Layer::Decoration => 1,
+            Layer::Collision => 2,
+            Layer::Overlay => 3,
+        }
+    }
+
+    pub fn is_visual(&self) -> bool {
+        !matches!(self, Layer::Collision)
+    }
+
+    pub fn above_actors(&self) -> bool {
+        matches!(self, Layer::Overlay)
+    }
+}
+
+pub fn empty_source() -> Rect {
+    Rect::new(0.0, 0.0, 0.0, 0.0)
+}
+
+pub fn is_empty_source(source: Rect) -> bool {
+    source.x == 0.0 && source.y == 0.0 && source.w == 0.0 && source.h == 0.0
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TileCell {
+    pub tile: Tile,
+    pub decoration: Rect,
+    pub overlay: Rect,
+    pub solid: bool,
+}
+
+impl TileCell {
+    pub fn source(&self, layer: Layer) -> Option<Rect> {
+        let source = match layer {
+            Layer::Ground => self.tile.source(),
+            Layer::Decoration => self.decoration,
+            Layer::Overlay => self.overlay,
+            Layer::Collision => return None,
+        };
+
+        if is_empty_source(source) {
+            None
+        } else {
+            Some(source)
+        }
+    }
+
+    pub fn set_source(&mut self, layer: Layer, source: Rect) {
+        match layer {
+            Layer::Ground => self.tile.set_source(source),
+            Layer::Decoration => self.decoration = source,
+            Layer::Overlay => self.overlay = source,
+            Layer::Collision => {}
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    pub const ALL: [Anchor; 9] = [
+        Anchor::TopLeft,
+        Anchor::Top,
+        Anchor::TopRight,
+        Anchor::Left,
+        Anchor::Center,
+        Anchor::Right,
+        Anchor::BottomLeft,
+        Anchor::Bottom,
+        Anchor::BottomRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Anchor::TopLeft => "TL",
+            Anchor::Top => "T",
+            Anchor::TopRight => "TR",
+            Anchor::Left => "L",
+            Anchor::Center => "C",
+            Anchor::Right => "R",
+            Anchor::BottomLeft => "BL",
+            Anchor::Bottom => "B",
+            Anchor::BottomRight => "BR",
+        }
+    }
+
+    pub fn offset(&self, old_size: (i32, i32), new_size: (i32, i32)) -> (i32, i32) {
+        let grow_x = new_size.0 - old_size.0;
+        let grow_y = new_size.1 - old_size.1;
+
+        let x = match self {
+            Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => 0,
+            Anchor::Top | Anchor::Center | Anchor::Bottom => grow_x / 2,
+            Anchor::TopRight | Anchor::Right | Anchor::BottomRight => grow_x,
+        };
+        let y = match self {
+            Anchor::TopLeft | Anchor::Top | Anchor::TopRight => 0,
+            Anchor::Left | Anchor::Center | Anchor::Right => grow_y / 2,
+            Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => grow_y,
+        };
+
+        (x, y)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileMap {
+    width: i32,
+    height: i32,
+    tiles: Vec<Tile>,
+    decoration: Vec<Rect>,
+    overlay: Vec<Rect>,
+    solid: Vec<bool>,
+}
+
+impl TileMap {
+    pub fn new(width: i32, height: i32) -> TileMap {
+        let width = width.clamp(0, MAX_MAP_SIZE);
+        let height = height.clamp(0, MAX_MAP_SIZE);
+
+        let mut tiles = Vec::with_capacity(cell_count(width, height).unwrap_or(0));
+        for row in 0..height {
+            for col in 0..width {
+                tiles.push(Tile::empty(grid_to_world(col, row)));
             }
         }
+
+        TileMap {
+            width,
+            height,
+            decoration: vec![empty_source(); tiles.len()],
+            overlay: vec![empty_source(); tiles.len()],
+            solid: vec![false; tiles.len()],
+            tiles,
+        }
     }
 
-    false
+    pub fn from_tiles(tiles: Vec<Tile>) -> TileMap {
+        let (width, height) = grid_extent(tiles.iter().map(Tile::pos));
+        let mut map = TileMap::new(width, height);
+
+        for tile in tiles.into_iter() {
+            let (col, row) = world_to_grid(tile.pos());
+            map.set_legacy_tile(col, row, tile);
+        }
+
+        map
+    }
+
+    pub fn set_legacy_tile(&mut self, col: i32, row: i32, tile: Tile) {
+        if let Some(index) = self.index(col, row) {
+            self.tiles[index] = tile;
+        }
+    }
+
+    pub fn resized(&self, width: i32, height: i32, anchor: Anchor) -> TileMap {
+        let mut map = TileMap::new(width, height);
+        let (dx, dy) = anchor.offset((self.width, self.height), (map.width, map.height));
+
+        for (col, row, tile) in self.cells() {
+            if let Some(index) = map.index(col + dx, row + dy) {
+                let from = (row * self.width + col) as usize;
+                map.tiles[index] = Tile::new(
+                    grid_to_world(col + dx, row + dy),
+                    tile.source(),
+                    tile.tile_type(),
+                );
+                map.decoration[index] = self.decoration[from];
+                map.overlay[index] = self.overlay[from];
+                map.solid[index] = self.solid[from];
+            }
+        }
+
+        map
+    }
+
+    pub fn cell(&self, col: i32, row: i32) -> Option<TileCell> {
+        self.index(col, row).map(|index| TileCell {
+            tile: self.tiles[index],
+            decoration: self.decoration[index],
+            overlay: self.overlay[index],
+            solid: self.solid[index],
+        })
+    }
+
+    pub fn set_cell(&mut self, col: i32, row: i32, cell: TileCell) {
+        if let Some(index) = self.index(col, row) {
+            self.tiles[index].set_source(cell.tile.source());
+            self.tiles[index].set_type(cell.tile.tile_type());
+            self.decoration[index] = cell.decoration;
+            self.overlay[index] = cell.overlay;
+            self.solid[index] = cell.solid;
+        }
+    }
+
+    pub fn layer_source(&self, layer: Layer, col: i32, row: i32) -> Option<Rect> {
+        self.cell(col, row).and_then(|cell| cell.source(layer))
+    }
+
+    pub fn set_layer_source(&mut self, layer: Layer, col: i32, row: i32, source: Rect) {
+        if let Some(mut cell) = self.cell(col, row) {
+            cell.set_source(layer, source);
+            self.set_cell(col, row, cell);
+        }
+    }
+
+    pub fn layer_cells(&self, layer: Layer) -> impl Iterator<Item = (i32, i32, Rect)> + '_ {
+        let width = self.width.max(1);
+        (0..self.tiles.len()).filter_map(move |index| {
+            let (col, row) = (index as i32 % width, index as i32 / width);
+            self.layer_source(layer, col, row)
+                .map(|source| (col, row, source))
+        })
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn in_bounds(&self, col: i32, row: i32) -> bool {
+        col >= 0 && row >= 0 && col < self.width && row < self.height
+    }
+
+    fn index(&self, col: i32, row: i32) -> Option<usize> {
+        if self.in_bounds(col, row) {
+            Some((row * self.width + col) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, col: i32, row: i32) -> Option<&Tile> {
+        self.index(col, row).map(|index| &self.tiles[index])
+    }
+
+    pub fn set_type(&mut self, col: i32, row: i32, tile_type: TileType) {
+        if let Some(index) = self.index(col, row) {
+            self.tiles[index].set_type(tile_type);
+        }
+    }
+
+    pub fn fill(&mut self, tile_type: TileType, source: Rect) {
+        for tile in self.tiles.iter_mut() {
+            tile.set_type(tile_type);
+            tile.set_source(source);
+        }
+    }
+
+    pub fn tile_at(&self, pos: Vec2) -> Option<&Tile> {
+        let (col, row) = self.world_to_grid(pos);
+        self.get(col, row)
+    }
+
+    pub fn world_to_grid(&self, pos: Vec2) -> (i32, i32) {
+        world_to_grid(pos)
+    }
+
+    pub fn grid_to_world(&self, col: i32, row: i32) -> Vec2 {
+        grid_to_world(col, row)
+    }
+
+    pub fn is_wall(&self, col: i32, row: i32) -> bool {
+        self.index(col, row)
+            .map(|index| self.solid[index])
+            .unwrap_or(false)
+    }
+
+    pub fn set_wall(&mut self, col: i32, row: i32, solid: bool) {
+        if let Some(index) = self.index(col, row) {
+            self.solid[index] = solid;
+        }
+    }
+
+    pub fn is_floor(&self, col: i32, row: i32) -> bool {
+        match self.get(col, row) {
+            Some(tile) if !self.is_wall(col, row) => {
+                matches!(tile.tile_type(), TileType::Floor(_)) || !is_empty_source(tile.source())
+            }
+            _ => false,
+        }
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Tile> {
+        self.tiles.iter()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32, &Tile)> + '_ {
+        let width = self.width.max(1);
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(move |(index, tile)| (index as i32 % width, index as i32 / width, tile))
+    }
 }
 
-pub fn calculate_tile_pos(x: f32, y: f32) -> Vec2 {
-    let result_x = (x as i32 / 32) * 32;
-    let result_y = (y as i32 / 32) * 32;
+pub fn world_to_grid(pos: Vec2) -> (i32, i32) {
+    (
+        (pos.x / TILE_SIZE).floor() as i32,
+        (pos.y / TILE_SIZE).floor() as i32,
+    )
+}
+
+pub fn grid_extent<I: Iterator<Item = Vec2>>(positions: I) -> (i32, i32) {
+    positions
+        .map(world_to_grid)
+        .fold((0, 0), |(width, height), (col, row)| {
+            (
+                width.max(col.saturating_add(1)),
+                height.max(row.saturating_add(1)),
+            )
+        })
+}
 
-    vec2(result_x as f32, result_y as f32)
+pub fn cell_count(width: i32, height: i32) -> Option<usize> {
+    width
+        .max(0)
+        .checked_mul(height.max(0))
+        .map(|cells| cells as usize)
+}
+
+pub fn grid_to_world(col: i32, row: i32) -> Vec2 {
+    vec2(col as f32 * TILE_SIZE, row as f32 * TILE_SIZE)
+}
+
+pub fn get_tile(x: f32, y: f32, tiles: &TileMap) -> bool {
+    let (col, row) = tiles.world_to_grid(vec2(x + 15.0, y + 15.0));
+
+    tiles.is_wall(col, row)
+}
+
+pub fn calculate_tile_pos(x: f32, y: f32) -> Vec2 {
+    let (col, row) = world_to_grid(vec2(x, y));
+    grid_to_world(col, row)
 }
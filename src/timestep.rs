@@ -0,0 +1,58 @@
+pub const FIXED_DT: f32 = 1.0 / 60.0;
+pub const MAX_FRAME_TIME: f32 = 0.25;
+const STEP_EPSILON: f32 = 1e-5;
+
+pub fn damp(factor: f32, dt: f32) -> f32 {
+    1.0 - (1.0 - factor).powf(dt * 60.0)
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(FIXED_DT)
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32) -> FixedTimestep {
+        assert!(step > 0.0, "fixed timestep must be positive, got {}", step);
+
+        FixedTimestep {
+            step,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    pub fn accumulator(&self) -> f32 {
+        self.accumulator
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        self.accumulator += frame_time.clamp(0.0, MAX_FRAME_TIME);
+
+        let mut steps = 0;
+        while self.accumulator + STEP_EPSILON >= self.step {
+            self.accumulator = (self.accumulator - self.step).max(0.0);
+            steps += 1;
+        }
+
+        steps
+    }
+
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+}
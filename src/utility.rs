@@ -1,4 +1,8 @@
+use crate::enemy::patrol::*;
+use crate::enemy::spawn::*;
 use crate::enemy::*;
+use crate::rng::*;
+use crate::serialization::*;
 use crate::tile::*;
 use macroquad::prelude::*;
 
@@ -27,22 +31,82 @@ pub async fn create_texture(path: &str) -> Result<Texture2D, String> {
     Ok(texture)
 }
 
-pub fn create_new_map(padding: (f32, f32), map_width: i32, map_height: i32) -> Vec<Tile> {
-    let mut tiles = Vec::new();
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnMove {
+    Unchanged,
+    Moved(i32, i32),
+    NoFloor,
+}
 
-    let x_padding = padding.0;
-    let y_padding = padding.1;
+pub fn resize_level(
+    level: &LevelData,
+    width: i32,
+    height: i32,
+    anchor: Anchor,
+) -> (LevelData, SpawnMove) {
+    let old_size = (level.tiles.width(), level.tiles.height());
+    let mut tiles = level.tiles.resized(width, height, anchor);
+    let (dx, dy) = anchor.offset(old_size, (tiles.width(), tiles.height()));
+    let shift = grid_to_world(dx, dy);
 
-    for y in 0..map_height {
-        for x in 0..map_width {
-            tiles.push(Tile::empty(Vec2::new(
-                x as f32 * x_padding * 32.0,
-                y as f32 * y_padding * 32.0,
-            )));
-        }
+    let in_bounds = |pos: Vec2| {
+        let (col, row) = world_to_grid(pos);
+        tiles.in_bounds(col, row)
+    };
+
+    let patrol_routes = level
+        .patrol_routes
+        .iter()
+        .map(|route| {
+            PatrolRoute::new(
+                route
+                    .waypoints()
+                    .iter()
+                    .map(|waypoint| *waypoint + shift)
+                    .filter(|waypoint| in_bounds(*waypoint))
+                    .collect(),
+            )
+        })
+        .filter(|route| !route.is_empty())
+        .collect();
+
+    let enemy_spawns = level
+        .enemy_spawns
+        .iter()
+        .map(|spawn| EnemySpawn::new(spawn.pos() + shift, spawn.kind(), spawn.count()))
+        .filter(|spawn| in_bounds(spawn.pos()))
+        .collect();
+
+    let mut spawn_move = SpawnMove::Unchanged;
+    let spawn_kept = tiles
+        .iter()
+        .any(|tile| matches!(tile.tile_type(), TileType::PlayerSpawn(_)));
+
+    if let (false, Some(old_spawn)) = (spawn_kept, level.player_spawn()) {
+        let (col, row) = world_to_grid(old_spawn);
+        let (col, row) = (col + dx, row + dy);
+
+        let nearest = tiles
+            .cells()
+            .filter(|(x, y, _)| tiles.is_floor(*x, *y))
+            .min_by_key(|(x, y, _)| (x - col).pow(2) + (y - row).pow(2))
+            .map(|(x, y, _)| (x, y));
+
+        spawn_move = match nearest {
+            Some((x, y)) => {
+                tiles.set_type(x, y, TileType::PlayerSpawn(3));
+                SpawnMove::Moved(x, y)
+            }
+            None => SpawnMove::NoFloor,
+        };
     }
 
-    tiles
+    let mut resized = LevelData::new(&level.name, tiles);
+    resized.atlas = level.atlas.clone();
+    resized.patrol_routes = patrol_routes;
+    resized.enemy_spawns = enemy_spawns;
+
+    (resized, spawn_move)
 }
 
 pub fn sub_image_valid(texture: &Texture2D, rect: Rect) -> bool {
@@ -56,7 +120,7 @@ pub fn sub_image_valid(texture: &Texture2D, rect: Rect) -> bool {
     let y = rect.y as usize;
     for y in y..y + height {
         for x in x..x + width {
-            let n_1 = y * texture_width as usize * 4 + x * 4 + 0;
+            let n_1 = y * texture_width as usize * 4 + x * 4;
             let n_2 = y * texture_width as usize * 4 + x * 4 + 1;
             let n_3 = y * texture_width as usize * 4 + x * 4 + 2;
             let n_4 = y * texture_width as usize * 4 + x * 4 + 3;
@@ -70,99 +134,128 @@ pub fn sub_image_valid(texture: &Texture2D, rect: Rect) -> bool {
     true
 }
 
-pub fn draw_map(tiles: &mut Vec<Tile>, tilemap: Texture2D, debug_collider: bool) {
-    for tile in tiles.iter() {
-        match tile.tile_type() {
-            TileType::Empty(_) => {
-                draw_rectangle_lines(tile.pos().x, tile.pos().y, 32.0, 32.0, 1.0, RED);
-            }
-            TileType::Floor(_) => {
-                draw_texture_ex(
-                    tilemap,
-                    tile.pos().x,
-                    tile.pos().y,
-                    WHITE,
-                    DrawTextureParams {
-                        dest_size: Option::Some(Vec2::new(32.0, 32.0)),
-                        source: Option::Some(Rect::new(
-                            tile.source().x,
-                            tile.source().y,
-                            tile.source().w,
-                            tile.source().h,
-                        )),
-                        ..Default::default()
-                    },
-                );
-            }
-            TileType::Wall(_) => {
-                if debug_collider {
-                    draw_rectangle_lines(tile.pos().x, tile.pos().y, 32.0, 32.0, 1.0, BLUE);
+pub fn draw_tile_source(tilemap: Texture2D, pos: Vec2, source: Rect) {
+    draw_texture_ex(
+        tilemap,
+        pos.x,
+        pos.y,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Option::Some(Vec2::new(32.0, 32.0)),
+            source: Option::Some(source),
+            ..Default::default()
+        },
+    );
+}
+
+pub fn draw_layer(tiles: &TileMap, tilemap: Texture2D, layer: Layer) {
+    draw_padded_layer(tiles, tilemap, layer, (1.0, 1.0));
+}
+
+pub fn draw_padded_layer(tiles: &TileMap, tilemap: Texture2D, layer: Layer, padding: (f32, f32)) {
+    let padded = |pos: Vec2| vec2(pos.x * padding.0, pos.y * padding.1);
+
+    match layer {
+        Layer::Ground => {
+            for tile in tiles.iter() {
+                let pos = padded(tile.pos());
+                if is_empty_source(tile.source()) {
+                    draw_rectangle_lines(pos.x, pos.y, 32.0, 32.0, 1.0, RED);
                 } else {
-                    draw_texture_ex(
-                        tilemap,
-                        tile.pos().x,
-                        tile.pos().y,
-                        WHITE,
-                        DrawTextureParams {
-                            dest_size: Option::Some(Vec2::new(32.0, 32.0)),
-                            source: Option::Some(Rect::new(
-                                tile.source().x,
-                                tile.source().y,
-                                tile.source().w,
-                                tile.source().h,
-                            )),
-                            ..Default::default()
-                        },
-                    );
+                    draw_tile_source(tilemap, pos, tile.source());
                 }
             }
-            TileType::PlayerSpawn(_) => {
-                if debug_collider {
-                    draw_rectangle(tile.pos().x, tile.pos().y, 32.0, 32.0, BLACK);
-                } else {
-                    draw_texture_ex(
-                        tilemap,
-                        tile.pos().x,
-                        tile.pos().y,
-                        WHITE,
-                        DrawTextureParams {
-                            dest_size: Option::Some(Vec2::new(32.0, 32.0)),
-                            source: Option::Some(Rect::new(
-                                tile.source().x,
-                                tile.source().y,
-                                tile.source().w,
-                                tile.source().h,
-                            )),
-                            ..Default::default()
-                        },
-                    );
+        }
+        Layer::Collision => {
+            for (col, row, tile) in tiles.cells() {
+                let pos = padded(tile.pos());
+                if tiles.is_wall(col, row) {
+                    draw_rectangle_lines(pos.x, pos.y, 32.0, 32.0, 1.0, BLUE);
+                } else if matches!(tile.tile_type(), TileType::PlayerSpawn(_)) {
+                    draw_rectangle(pos.x, pos.y, 32.0, 32.0, BLACK);
                 }
             }
         }
+        Layer::Decoration | Layer::Overlay => {
+            for (col, row, source) in tiles.layer_cells(layer) {
+                draw_tile_source(tilemap, padded(grid_to_world(col, row)), source);
+            }
+        }
+    }
+}
+
+pub fn draw_map(tiles: &TileMap, tilemap: Texture2D, debug_collider: bool) {
+    for layer in Layer::ALL.iter().filter(|layer| !layer.above_actors()) {
+        if layer.is_visual() || debug_collider {
+            draw_layer(tiles, tilemap, *layer);
+        }
+    }
+}
+
+pub fn draw_overlay(tiles: &TileMap, tilemap: Texture2D) {
+    for layer in Layer::ALL.iter().filter(|layer| layer.above_actors()) {
+        draw_layer(tiles, tilemap, *layer);
     }
 }
 
 pub fn spawn_enemy(
-    tiles: &Vec<Tile>,
+    tiles: &TileMap,
     enemies: &mut Vec<Enemy>,
     player_pos: Vec2,
     enemy_count: usize,
+    rng: &mut GameRng,
 ) {
     const FACTOR: f32 = 32.0 * 5.0;
 
-    for tile in tiles.iter() {
-        if let TileType::Floor(_) = tile.tile_type() {
-            if rand::gen_range(0.0, 5.0) < 1.0 {
-                if tile.pos().x > player_pos.x + FACTOR
-                    || tile.pos().x < player_pos.x - FACTOR
-                    || tile.pos().y > player_pos.y + FACTOR
-                    || tile.pos().y < player_pos.y - FACTOR
-                {
-                    if enemies.len() < enemy_count {
-                        enemies.push(Enemy::new(tile.pos()));
-                    }
-                }
-            }
+    for (col, row, tile) in tiles.cells() {
+        if tiles.is_floor(col, row)
+            && rng.gen_range(0.0, 5.0) < 1.0
+            && (tile.pos().x > player_pos.x + FACTOR
+                || tile.pos().x < player_pos.x - FACTOR
+                || tile.pos().y > player_pos.y + FACTOR
+                || tile.pos().y < player_pos.y - FACTOR)
+            && enemies.len() < enemy_count
+        {
+            enemies.push(Enemy::new(tile.pos(), EnemyKind::Sword, rng));
+        }
+    }
+}
+
+pub fn spawn_placed_enemies(
+    tiles: &TileMap,
+    spawns: &[EnemySpawn],
+    enemies: &mut Vec<Enemy>,
+    rng: &mut GameRng,
+) {
+    for spawn in spawns.iter() {
+        for pos in spawn.spawn_positions(tiles) {
+            enemies.push(Enemy::new(pos, spawn.kind(), rng));
         }
     }
 }
+
+pub fn spawn_patrol_enemies(routes: &[PatrolRoute], enemies: &mut Vec<Enemy>, rng: &mut GameRng) {
+    for route in routes.iter() {
+        if let Some(start) = route.waypoint(0) {
+            let mut enemy = Enemy::new(start, EnemyKind::Sword, rng);
+            enemy.set_patrol(Some(route.clone()));
+            enemies.push(enemy);
+        }
+    }
+}
+
+pub fn parse_arg<I: Iterator<Item = String>>(mut args: I, name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+
+        if arg == name {
+            return args.next();
+        }
+    }
+
+    None
+}
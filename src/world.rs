@@ -0,0 +1,123 @@
+use crate::enemy::ai::*;
+use crate::enemy::flow_field::*;
+use crate::enemy::navigation::*;
+use crate::enemy::patrol::*;
+use crate::enemy::spawn::*;
+use crate::enemy::*;
+use crate::input::*;
+use crate::player::*;
+use crate::rng::*;
+use crate::tile::*;
+use crate::utility::*;
+
+pub const RANDOM_ENEMY_COUNT: usize = 5;
+
+pub struct World {
+    tiles: TileMap,
+    nav_grid: NavGrid,
+    flow_field: FlowField,
+    player: Player,
+    enemies: Vec<Enemy>,
+    rng: GameRng,
+}
+
+impl World {
+    pub fn new(tiles: TileMap, seed: u64) -> World {
+        let mut player = Player::new(20.0, 20.0, 32.0, 32.0, 100.0);
+        player.spawn_player(&tiles);
+
+        World {
+            nav_grid: NavGrid::from_tile_map(&tiles),
+            flow_field: FlowField::new(EnemyKind::Sword.neighbor_mode()),
+            tiles,
+            player,
+            enemies: Vec::new(),
+            rng: GameRng::new(seed),
+        }
+    }
+
+    pub fn tiles(&self) -> &TileMap {
+        &self.tiles
+    }
+
+    pub fn nav_grid(&self) -> &NavGrid {
+        &self.nav_grid
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn player_mut(&mut self) -> &mut Player {
+        &mut self.player
+    }
+
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    pub fn enemies_mut(&mut self) -> &mut Vec<Enemy> {
+        &mut self.enemies
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    pub fn player_dead(&self) -> bool {
+        self.player.health() <= 0
+    }
+
+    pub fn spawn_enemies(
+        &mut self,
+        spawns: &[EnemySpawn],
+        patrol_routes: &[PatrolRoute],
+        random_fallback: bool,
+    ) {
+        spawn_placed_enemies(&self.tiles, spawns, &mut self.enemies, &mut self.rng);
+        if self.enemies.is_empty() && random_fallback {
+            spawn_enemy(
+                &self.tiles,
+                &mut self.enemies,
+                self.player.pos(),
+                RANDOM_ENEMY_COUNT,
+                &mut self.rng,
+            );
+        }
+        spawn_patrol_enemies(patrol_routes, &mut self.enemies, &mut self.rng);
+    }
+
+    pub fn step(&mut self, input: &InputSnapshot, dt: f32) {
+        self.flow_field.update(&self.nav_grid, self.player.pos());
+
+        for enemy in self.enemies.iter_mut() {
+            enemy.update(
+                self.player.pos(),
+                &self.tiles,
+                &self.nav_grid,
+                Some(&self.flow_field),
+                dt,
+            );
+            enemy.damage_player(&mut self.player);
+        }
+
+        self.enemies.retain(|enemy| enemy.state() != AiState::Dead);
+
+        let enemy_count = self.enemies.len();
+        let enemy_list_cloned = self.enemies.clone();
+        let mut enemies = self.enemies.iter_mut();
+
+        for i in 0..enemy_count {
+            if let Some(enemy) = enemies.next() {
+                for (index, enemy2) in enemy_list_cloned.iter().enumerate() {
+                    if i != index {
+                        enemy.displace(enemy2, dt);
+                    }
+                }
+            }
+        }
+
+        self.player
+            .update(input, &self.tiles, &mut self.enemies, dt);
+    }
+}
@@ -0,0 +1,33 @@
+#![allow(dead_code)]
+
+use dungeon_game::enemy::navigation::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+pub fn scratch_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("dungeon_game_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.to_str().unwrap().to_string()
+}
+
+pub fn spawn_room(width: i32, height: i32) -> TileMap {
+    let mut tiles = TileMap::new(width, height);
+    tiles.fill(TileType::Floor(1), Rect::new(16.0, 0.0, 16.0, 16.0));
+    tiles.set_type(1, 1, TileType::PlayerSpawn(3));
+
+    tiles
+}
+
+pub fn path_cost(mode: NeighborMode, start: Vec2, path: &Path) -> u32 {
+    let mut previous = world_to_grid(start);
+    let mut cost = 0;
+
+    for waypoint in path.waypoints().iter() {
+        let cell = world_to_grid(*waypoint);
+        cost += mode.step_cost(previous, cell);
+        previous = cell;
+    }
+
+    cost
+}
@@ -0,0 +1,189 @@
+use dungeon_game::serialization::compact::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+const LEGACY_LEVELS: [&str; 4] = ["level_0", "ai_test", "gigachad", "coolest_level"];
+
+fn legacy_contents(name: &str) -> String {
+    std::fs::read_to_string(level_path(name)).unwrap()
+}
+
+fn legacy_tiles(name: &str) -> TileMap {
+    Level::parse(&legacy_contents(name), name)
+        .unwrap()
+        .into_data()
+        .tiles
+}
+
+fn as_loaded_tiles(tiles: &TileMap) -> serde_json::Value {
+    let walls: Vec<bool> = tiles
+        .cells()
+        .map(|(col, row, _)| tiles.is_wall(col, row))
+        .collect();
+    serde_json::to_value((tiles.tiles(), walls)).unwrap()
+}
+
+#[test]
+fn grid_layers_round_trip_raw_and_rle() {
+    let values = vec![1, 1, 1, 2, 2, -1, 3, 3, 3, 3];
+
+    assert_eq!(GridLayer::raw(&values).decode(), values);
+    assert_eq!(GridLayer::rle(&values).decode(), values);
+    assert_eq!(GridLayer::rle(&values).len(), values.len());
+    assert_eq!(
+        GridLayer::rle(&values),
+        GridLayer::Rle(vec![(1, 3), (2, 2), (-1, 1), (3, 4)])
+    );
+}
+
+#[test]
+fn encoding_picks_the_shorter_layer() {
+    assert!(GridLayer::encode(&[7; 64]).is_rle());
+    assert!(!GridLayer::encode(&[1, 2, 3, 4, 5, 6]).is_rle());
+}
+
+#[test]
+fn legacy_levels_match_the_legacy_tile_representation() {
+    for name in LEGACY_LEVELS {
+        let legacy = legacy_tiles(name);
+        let walls = legacy_contents(name).matches("\"Wall\"").count();
+
+        let compact = CompactTiles::encode(&legacy).unwrap();
+        let decoded = compact.decode(legacy.width(), legacy.height());
+
+        assert_eq!(
+            decoded
+                .cells()
+                .filter(|(col, row, _)| decoded.is_wall(*col, *row))
+                .count(),
+            walls,
+            "{} lost walls after compact round trip",
+            name
+        );
+        assert_eq!(
+            as_loaded_tiles(&decoded),
+            as_loaded_tiles(&legacy),
+            "{} changed after compact round trip",
+            name
+        );
+    }
+}
+
+#[test]
+fn shipped_bare_array_levels_load_and_resave_as_compact() {
+    for name in LEGACY_LEVELS {
+        assert!(legacy_contents(name).trim_start().starts_with('['));
+
+        let level = load(name).unwrap();
+        let saved = serde_json::to_string(&Level::from_data(&level)).unwrap();
+        let reloaded = Level::parse(&saved, name).unwrap();
+
+        assert!(reloaded.compact_tiles().is_some());
+        assert_eq!(
+            as_loaded_tiles(&reloaded.into_data().tiles),
+            as_loaded_tiles(&level.tiles),
+            "{} changed after saving as compact",
+            name
+        );
+    }
+}
+
+#[test]
+fn compact_documents_are_much_smaller() {
+    let legacy = legacy_contents("level_0");
+    let data = LevelData::new("level_0", legacy_tiles("level_0"));
+    let compact = serde_json::to_string_pretty(&Level::from_data(&data)).unwrap();
+
+    assert!(Level::from_data(&data).compact_tiles().is_some());
+    assert!(compact.len() * 4 < legacy.len());
+}
+
+#[test]
+fn unaligned_sources_fall_back_to_explicit_tiles() {
+    let mut tiles = TileMap::new(3, 3);
+    tiles.fill(TileType::Floor(1), Rect::new(16.0, 16.0, 16.0, 16.0));
+    tiles.set_layer_source(Layer::Ground, 1, 1, Rect::new(5.0, 0.0, 16.0, 16.0));
+
+    let data = LevelData::new("unaligned", tiles);
+    let level = Level::from_data(&data);
+    assert!(level.compact_tiles().is_none());
+
+    let json = serde_json::to_string(&level).unwrap();
+    let loaded = Level::parse(&json, "unaligned").unwrap().into_data();
+    assert_eq!(as_loaded_tiles(&loaded.tiles), as_loaded_tiles(&data.tiles));
+}
+
+#[test]
+fn layers_with_the_wrong_cell_count_are_rejected() {
+    let json = r#"{
+        "version": 3, "name": "short", "width": 3, "height": 3, "tile_size": 32.0,
+        "atlas": "res/textures/tilemap_packed.png", "player_spawn": null,
+        "tiles": {
+            "atlas_tile_size": 16.0, "atlas_columns": 12,
+            "atlas": {"Rle": [[0, 8]]},
+            "collision": {"Rle": [[1, 9]]}
+        }
+    }"#;
+
+    assert!(Level::parse(json, "short").is_err());
+}
+
+#[test]
+fn solid_cells_are_a_separate_layer_for_any_tile_type() {
+    let mut tiles = TileMap::new(3, 1);
+    tiles.set_type(0, 0, TileType::Floor(7));
+    tiles.set_type(1, 0, TileType::PlayerSpawn(3));
+    tiles.set_wall(0, 0, true);
+    tiles.set_wall(2, 0, true);
+
+    let compact = CompactTiles::encode(&tiles).unwrap();
+    let decoded = compact.decode(3, 1);
+
+    assert_eq!(compact.solid().decode(), vec![1, 0, 1]);
+    assert_eq!(
+        compact.types().decode(),
+        vec![FLOOR_CODE, SPAWN_CODE, EMPTY_CODE]
+    );
+    assert!(compact.payloads().is_some());
+    assert_eq!(as_loaded_tiles(&decoded), as_loaded_tiles(&tiles));
+}
+
+#[test]
+fn default_payloads_are_not_stored() {
+    let compact = CompactTiles::encode(&legacy_tiles("level_0")).unwrap();
+
+    assert!(compact.payloads().is_none());
+}
+
+#[test]
+fn packed_collision_codes_migrate_to_separate_layers() {
+    let json = r#"{
+        "version": 4, "name": "packed", "width": 4, "height": 1, "tile_size": 32.0,
+        "atlas": "res/textures/tilemap_packed.png", "player_spawn": null,
+        "tiles": {
+            "atlas_tile_size": 16.0, "atlas_columns": 12,
+            "atlas": {"Raw": [-1, 0, 0, 0]},
+            "collision": {"Raw": [0, 1, 2, 3]}
+        }
+    }"#;
+
+    let level = Level::parse(json, "packed").unwrap();
+    let compact = level.compact_tiles().unwrap();
+    assert_eq!(compact.solid().decode(), vec![0, 0, 1, 0]);
+
+    let tiles = level.into_data().tiles;
+    let types: Vec<TileType> = tiles.iter().map(|tile| tile.tile_type()).collect();
+    assert_eq!(
+        types,
+        vec![
+            TileType::Empty(0),
+            TileType::Floor(1),
+            TileType::Floor(1),
+            TileType::PlayerSpawn(3)
+        ]
+    );
+    assert!(tiles.is_wall(2, 0));
+    assert!(!tiles.is_wall(1, 0));
+}
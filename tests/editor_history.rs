@@ -0,0 +1,229 @@
+use dungeon_game::editor::history::*;
+use dungeon_game::enemy::patrol::*;
+use dungeon_game::enemy::spawn::*;
+use dungeon_game::enemy::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+struct Document {
+    tiles: TileMap,
+    patrol_routes: Vec<PatrolRoute>,
+    enemy_spawns: Vec<EnemySpawn>,
+    history: History,
+}
+
+impl Document {
+    fn new(width: i32, height: i32) -> Document {
+        Document {
+            tiles: TileMap::new(width, height),
+            patrol_routes: Vec::new(),
+            enemy_spawns: Vec::new(),
+            history: History::new(),
+        }
+    }
+
+    fn paint(&mut self, col: i32, row: i32, tile_type: TileType) {
+        let before = self.tiles.cell(col, row).unwrap();
+        let mut after = before;
+        after.tile.set_type(tile_type);
+        after.tile.set_source(Rect::new(16.0, 0.0, 16.0, 16.0));
+        self.tiles.set_cell(col, row, after);
+        self.history.record_cell(col, row, before, after);
+    }
+
+    fn wall(&mut self, col: i32, row: i32) {
+        let before = self.tiles.cell(col, row).unwrap();
+        let mut after = before;
+        after.solid = true;
+        self.tiles.set_cell(col, row, after);
+        self.history.record_cell(col, row, before, after);
+    }
+
+    fn undo(&mut self) -> bool {
+        self.history.undo(
+            &mut self.tiles,
+            &mut self.patrol_routes,
+            &mut self.enemy_spawns,
+        )
+    }
+
+    fn redo(&mut self) -> bool {
+        self.history.redo(
+            &mut self.tiles,
+            &mut self.patrol_routes,
+            &mut self.enemy_spawns,
+        )
+    }
+
+    fn tile_type(&self, col: i32, row: i32) -> TileType {
+        self.tiles.get(col, row).unwrap().tile_type()
+    }
+}
+
+#[test]
+fn a_drag_stroke_undoes_as_one_command() {
+    let mut doc = Document::new(5, 5);
+
+    for col in 0..4 {
+        doc.paint(col, 2, TileType::Floor(1));
+        doc.paint(col, 2, TileType::Floor(1));
+    }
+    doc.history.end_stroke();
+
+    assert_eq!(doc.history.undo_len(), 1);
+    assert!(doc.undo());
+    assert!((0..4).all(|col| doc.tile_type(col, 2) == TileType::Empty(0)));
+    assert!(doc.tiles.get(0, 2).unwrap().source().w == 0.0);
+
+    assert!(doc.redo());
+    assert!((0..4).all(|col| doc.tile_type(col, 2) == TileType::Floor(1)));
+}
+
+#[test]
+fn repainting_a_cell_keeps_the_original_before_state() {
+    let mut doc = Document::new(3, 3);
+
+    doc.paint(1, 1, TileType::Floor(1));
+    doc.wall(1, 1);
+    doc.history.end_stroke();
+    doc.undo();
+
+    assert_eq!(doc.tile_type(1, 1), TileType::Empty(0));
+    assert!(!doc.tiles.is_wall(1, 1));
+}
+
+#[test]
+fn strokes_without_changes_are_not_recorded() {
+    let mut doc = Document::new(3, 3);
+
+    doc.paint(0, 0, TileType::Floor(1));
+    doc.history.end_stroke();
+    doc.paint(0, 0, TileType::Floor(1));
+    doc.history.end_stroke();
+
+    assert_eq!(doc.history.undo_len(), 1);
+}
+
+#[test]
+fn undo_closes_an_open_stroke_first() {
+    let mut doc = Document::new(3, 3);
+
+    doc.wall(2, 2);
+    assert!(doc.history.in_stroke());
+    assert!(doc.history.can_undo());
+
+    assert!(doc.undo());
+    assert_eq!(doc.tile_type(2, 2), TileType::Empty(0));
+    assert!(!doc.history.in_stroke());
+}
+
+#[test]
+fn new_edits_clear_the_redo_stack() {
+    let mut doc = Document::new(3, 3);
+
+    doc.paint(0, 0, TileType::Floor(1));
+    doc.history.end_stroke();
+    doc.undo();
+    assert!(doc.history.can_redo());
+
+    doc.wall(1, 0);
+    doc.history.end_stroke();
+
+    assert!(!doc.history.can_redo());
+    assert!(!doc.redo());
+}
+
+#[test]
+fn enemy_spawn_edits_round_trip() {
+    let mut doc = Document::new(4, 4);
+    let spawn = EnemySpawn::new(vec2(32.0, 32.0), EnemyKind::Sword, 2);
+
+    doc.enemy_spawns.push(spawn);
+    doc.history.push(EditCommand::EnemySpawns {
+        before: Vec::new(),
+        after: vec![spawn],
+    });
+
+    doc.undo();
+    assert!(doc.enemy_spawns.is_empty());
+    doc.redo();
+    assert_eq!(doc.enemy_spawns, vec![spawn]);
+}
+
+#[test]
+fn map_resize_restores_tiles_routes_and_spawns() {
+    let mut doc = Document::new(4, 4);
+    doc.paint(1, 1, TileType::PlayerSpawn(3));
+    doc.history.end_stroke();
+    doc.patrol_routes
+        .push(PatrolRoute::new(vec![vec2(0.0, 0.0), vec2(64.0, 0.0)]));
+    doc.enemy_spawns
+        .push(EnemySpawn::new(vec2(64.0, 64.0), EnemyKind::Sword, 1));
+
+    let before = MapSnapshot::new(&doc.tiles, &doc.patrol_routes, &doc.enemy_spawns);
+    doc.tiles = TileMap::new(10, 8);
+    doc.patrol_routes.clear();
+    doc.enemy_spawns.clear();
+    let after = MapSnapshot::new(&doc.tiles, &doc.patrol_routes, &doc.enemy_spawns);
+    doc.history.push(EditCommand::Resize {
+        before: Box::new(before),
+        after: Box::new(after),
+    });
+
+    doc.undo();
+    assert_eq!((doc.tiles.width(), doc.tiles.height()), (4, 4));
+    assert_eq!(doc.tile_type(1, 1), TileType::PlayerSpawn(3));
+    assert_eq!(doc.patrol_routes.len(), 1);
+    assert_eq!(doc.enemy_spawns.len(), 1);
+
+    doc.redo();
+    assert_eq!((doc.tiles.width(), doc.tiles.height()), (10, 8));
+    assert!(doc.patrol_routes.is_empty());
+
+    doc.undo();
+    doc.undo();
+    assert_eq!(doc.tile_type(1, 1), TileType::Empty(0));
+}
+
+#[test]
+fn routes_added_after_a_resize_survive_undo_and_redo() {
+    let mut doc = Document::new(4, 4);
+
+    let before = MapSnapshot::new(&doc.tiles, &doc.patrol_routes, &doc.enemy_spawns);
+    doc.tiles = TileMap::new(6, 6);
+    let after = MapSnapshot::new(&doc.tiles, &doc.patrol_routes, &doc.enemy_spawns);
+    doc.history.push(EditCommand::Resize {
+        before: Box::new(before),
+        after: Box::new(after),
+    });
+
+    let route = PatrolRoute::new(vec![vec2(0.0, 0.0), vec2(160.0, 160.0)]);
+    doc.patrol_routes.push(route.clone());
+    doc.history.push(EditCommand::PatrolRoutes {
+        before: Vec::new(),
+        after: vec![route.clone()],
+    });
+
+    doc.undo();
+    doc.undo();
+    assert_eq!((doc.tiles.width(), doc.tiles.height()), (4, 4));
+    assert!(doc.patrol_routes.is_empty());
+
+    doc.redo();
+    doc.redo();
+    assert_eq!((doc.tiles.width(), doc.tiles.height()), (6, 6));
+    assert_eq!(doc.patrol_routes, vec![route]);
+}
+
+#[test]
+fn history_is_capped() {
+    let mut doc = Document::new(MAX_HISTORY as i32 + 10, 1);
+
+    for col in 0..MAX_HISTORY as i32 + 10 {
+        doc.paint(col, 0, TileType::Floor(1));
+        doc.history.end_stroke();
+    }
+
+    assert_eq!(doc.history.undo_len(), MAX_HISTORY);
+}
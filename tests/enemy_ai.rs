@@ -0,0 +1,259 @@
+use dungeon_game::enemy::ai::*;
+use dungeon_game::enemy::navigation::*;
+use dungeon_game::enemy::patrol::*;
+use dungeon_game::enemy::*;
+use dungeon_game::rng::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+const DT: f32 = 1.0 / 60.0;
+
+fn perception(visible: bool, distance: f32) -> Perception {
+    Perception {
+        target_visible: visible,
+        target_distance: distance,
+        has_last_seen: true,
+        has_patrol: false,
+        health: 3,
+    }
+}
+
+fn open_room() -> (TileMap, NavGrid) {
+    let mut tiles = TileMap::new(12, 12);
+    tiles.fill(TileType::Floor(1), empty_source());
+
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+    (tiles, nav_grid)
+}
+
+#[test]
+fn spotting_the_target_starts_a_chase() {
+    let mut ai = StateMachine::new();
+
+    assert_eq!(ai.update(&perception(false, 300.0), DT), AiState::Idle);
+    assert_eq!(ai.update(&perception(true, 150.0), DT), AiState::Chase);
+}
+
+#[test]
+fn calm_state_depends_on_patrol_route() {
+    let mut ai = StateMachine::new();
+    let mut patrolling = perception(false, 300.0);
+    patrolling.has_patrol = true;
+
+    assert_eq!(ai.update(&patrolling, DT), AiState::Patrol);
+    assert_eq!(ai.update(&perception(true, 100.0), DT), AiState::Chase);
+}
+
+#[test]
+fn losing_sight_searches_then_gives_up() {
+    let mut ai = StateMachine::new();
+    ai.update(&perception(true, 150.0), DT);
+
+    assert_eq!(ai.update(&perception(false, 150.0), DT), AiState::Search);
+
+    let mut elapsed = 0.0;
+    while elapsed + DT < SEARCH_DURATION {
+        assert_eq!(ai.update(&perception(false, 150.0), DT), AiState::Search);
+        elapsed += DT;
+    }
+
+    assert_eq!(ai.update(&perception(false, 150.0), DT), AiState::Idle);
+}
+
+#[test]
+fn attacks_respect_the_cooldown() {
+    let mut ai = StateMachine::new();
+    ai.update(&perception(true, 40.0), DT);
+
+    let mut state = AiState::Chase;
+    for _ in 0..60 {
+        state = ai.update(&perception(true, 40.0), DT);
+        if state == AiState::Attack {
+            break;
+        }
+    }
+    assert_eq!(state, AiState::Attack);
+
+    ai.finish_attack();
+    assert_eq!(ai.state(), AiState::Chase);
+    assert!(!ai.attack_ready());
+
+    let mut waited = 0.0;
+    while ai.update(&perception(true, 40.0), DT) != AiState::Attack {
+        waited += DT;
+        assert!(waited <= ATTACK_COOLDOWN + DT);
+    }
+    assert!(waited >= ATTACK_COOLDOWN - 2.0 * DT);
+}
+
+#[test]
+fn low_health_flees_and_zero_health_dies() {
+    let mut ai = StateMachine::new();
+    let mut hurt = perception(true, 80.0);
+    hurt.health = FLEE_HEALTH;
+
+    assert_eq!(ai.update(&hurt, DT), AiState::Flee);
+
+    hurt.target_visible = false;
+    hurt.target_distance = FLEE_DISTANCE + 10.0;
+    assert_eq!(ai.update(&hurt, DT), AiState::Idle);
+
+    hurt.health = 0;
+    assert_eq!(ai.update(&hurt, DT), AiState::Dead);
+    assert_eq!(ai.update(&perception(true, 10.0), DT), AiState::Dead);
+}
+
+#[test]
+fn enemy_chases_and_attacks_a_visible_player() {
+    let (tiles, nav_grid) = open_room();
+    let mut enemy = Enemy::new(grid_to_world(2, 2), EnemyKind::Sword, &mut GameRng::new(0));
+    let player = grid_to_world(2, 6);
+
+    let start_distance = enemy.pos().distance(player);
+    assert_eq!(
+        enemy.update(player, &tiles, &nav_grid, None, DT),
+        AiState::Chase
+    );
+
+    let mut attacked = false;
+    for _ in 0..240 {
+        if enemy.update(player, &tiles, &nav_grid, None, DT) == AiState::Attack {
+            attacked = true;
+            break;
+        }
+    }
+
+    assert!(attacked);
+    assert!(enemy.pos().distance(player) < start_distance);
+    assert_eq!(enemy.last_seen_pos(), Some(player));
+}
+
+#[test]
+fn enemy_ignores_player_behind_walls_or_outside_the_cone() {
+    let (mut tiles, _) = open_room();
+    for row in 0..12 {
+        tiles.set_wall(5, row, true);
+    }
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let mut enemy = Enemy::new(grid_to_world(3, 4), EnemyKind::Sword, &mut GameRng::new(0));
+    enemy.set_facing(Vec2::X);
+
+    assert_eq!(
+        enemy.update(grid_to_world(7, 4), &tiles, &nav_grid, None, DT),
+        AiState::Idle
+    );
+    assert_eq!(
+        enemy.update(grid_to_world(1, 4), &tiles, &nav_grid, None, DT),
+        AiState::Idle
+    );
+    assert_eq!(
+        enemy.update(grid_to_world(4, 4), &tiles, &nav_grid, None, DT),
+        AiState::Chase
+    );
+}
+
+#[test]
+fn enemy_investigates_last_seen_position() {
+    let (mut tiles, _) = open_room();
+    for row in 3..12 {
+        tiles.set_wall(6, row, true);
+    }
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let mut enemy = Enemy::new(grid_to_world(2, 8), EnemyKind::Sword, &mut GameRng::new(0));
+    enemy.set_facing(Vec2::X);
+
+    let seen_at = grid_to_world(5, 6);
+    enemy.update(seen_at, &tiles, &nav_grid, None, DT);
+    assert_eq!(enemy.last_seen_pos(), Some(seen_at));
+
+    let hidden = grid_to_world(9, 9);
+    assert_eq!(
+        enemy.update(hidden, &tiles, &nav_grid, None, DT),
+        AiState::Search
+    );
+
+    let mut state = AiState::Search;
+    for _ in 0..600 {
+        state = enemy.update(hidden, &tiles, &nav_grid, None, DT);
+        if state != AiState::Search {
+            break;
+        }
+    }
+
+    assert_eq!(state, AiState::Idle);
+    assert!(enemy.pos().distance(seen_at) < 60.0);
+    assert_eq!(enemy.last_seen_pos(), None);
+}
+
+#[test]
+fn wounded_enemy_flees_then_dies() {
+    let (tiles, nav_grid) = open_room();
+    let mut enemy = Enemy::new(grid_to_world(5, 5), EnemyKind::Sword, &mut GameRng::new(0));
+    let player = grid_to_world(5, 8);
+
+    enemy.take_damage();
+    for _ in 0..100 {
+        enemy.update(player, &tiles, &nav_grid, None, DT);
+    }
+
+    enemy.take_damage();
+    assert_eq!(enemy.health(), FLEE_HEALTH);
+
+    let distance = enemy.pos().distance(player);
+    assert_eq!(
+        enemy.update(player, &tiles, &nav_grid, None, DT),
+        AiState::Flee
+    );
+    for _ in 0..30 {
+        enemy.update(player, &tiles, &nav_grid, None, DT);
+    }
+    assert!(enemy.pos().distance(player) > distance);
+
+    for _ in 0..100 {
+        enemy.update(player, &tiles, &nav_grid, None, DT);
+    }
+    enemy.take_damage();
+    assert_eq!(
+        enemy.update(player, &tiles, &nav_grid, None, DT),
+        AiState::Dead
+    );
+}
+
+#[test]
+fn enemy_walks_its_patrol_route() {
+    let (mut tiles, _) = open_room();
+    for row in 0..8 {
+        tiles.set_wall(6, row, true);
+    }
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let mut enemy = Enemy::new(grid_to_world(2, 2), EnemyKind::Sword, &mut GameRng::new(0));
+    enemy.set_patrol(Some(PatrolRoute::new(vec![
+        grid_to_world(2, 2),
+        grid_to_world(9, 2),
+    ])));
+
+    let hidden = grid_to_world(100, 100);
+    let mut visited_far_end = false;
+
+    for _ in 0..1200 {
+        assert_eq!(
+            enemy.update(hidden, &tiles, &nav_grid, None, DT),
+            AiState::Patrol
+        );
+        let (col, row) = world_to_grid(enemy.pos() + vec2(15.0, 15.0));
+        assert!(!tiles.is_wall(col, row));
+
+        if enemy.pos().distance(grid_to_world(9, 2)) < 4.0 {
+            visited_far_end = true;
+        }
+        if visited_far_end && enemy.patrol_target() == Some(grid_to_world(2, 2)) {
+            return;
+        }
+    }
+
+    panic!("enemy never completed its patrol loop");
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::*;
+use dungeon_game::enemy::spawn::*;
+use dungeon_game::enemy::*;
+use dungeon_game::rng::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+use dungeon_game::utility::*;
+
+use macroquad::prelude::*;
+
+fn walled_room() -> TileMap {
+    let mut tiles = TileMap::new(6, 6);
+    tiles.fill(TileType::Floor(1), empty_source());
+    for i in 0..6 {
+        for (col, row) in [(i, 0), (i, 5), (0, i), (5, i)] {
+            tiles.set_wall(col, row, true);
+        }
+    }
+
+    tiles
+}
+
+#[test]
+fn spawn_places_enemies_on_distinct_floor_tiles() {
+    let tiles = walled_room();
+    let spawn = EnemySpawn::new(grid_to_world(1, 1), EnemyKind::Sword, 4);
+
+    let positions = spawn.spawn_positions(&tiles);
+
+    assert_eq!(positions.len(), 4);
+    assert_eq!(positions[0], grid_to_world(1, 1));
+    for (index, pos) in positions.iter().enumerate() {
+        assert!(matches!(
+            tiles.tile_at(*pos).map(|tile| tile.tile_type()),
+            Some(TileType::Floor(_))
+        ));
+        assert!(!positions[index + 1..].contains(pos));
+    }
+}
+
+#[test]
+fn spawn_count_is_limited_by_floor_space() {
+    let tiles = walled_room();
+    let spawn = EnemySpawn::new(grid_to_world(2, 2), EnemyKind::Sword, MAX_SPAWN_COUNT);
+
+    assert_eq!(spawn.count(), MAX_SPAWN_COUNT);
+    assert_eq!(
+        spawn.spawn_positions(&tiles).len(),
+        MAX_SPAWN_COUNT as usize
+    );
+
+    let mut small = TileMap::new(3, 3);
+    small.set_type(1, 1, TileType::Floor(1));
+    let spawn = EnemySpawn::new(grid_to_world(0, 0), EnemyKind::Sword, 3);
+
+    assert_eq!(spawn.spawn_positions(&small), vec![grid_to_world(1, 1)]);
+}
+
+#[test]
+fn placed_spawns_create_enemies_of_their_kind() {
+    let tiles = walled_room();
+    let spawns = vec![
+        EnemySpawn::new(grid_to_world(1, 1), EnemyKind::Sword, 2),
+        EnemySpawn::new(grid_to_world(4, 4), EnemyKind::Sword, 1),
+    ];
+
+    let mut enemies = Vec::new();
+    spawn_placed_enemies(&tiles, &spawns, &mut enemies, &mut GameRng::new(0));
+
+    assert_eq!(enemies.len(), 3);
+    assert!(enemies.iter().all(|enemy| enemy.kind() == EnemyKind::Sword));
+    assert_eq!(enemies[2].pos(), grid_to_world(4, 4));
+}
+
+#[test]
+fn enemy_spawns_survive_save_and_load() {
+    let spawns = vec![EnemySpawn::new(vec2(64.0, 96.0), EnemyKind::Sword, 3)];
+    let dir = scratch_dir("enemy_spawn_round_trip");
+    let path = "spawns";
+    let mut level = LevelData::new(path, walled_room());
+    level.enemy_spawns = spawns.clone();
+
+    save_to(&dir, &level, path).unwrap();
+    let level = load_from(&dir, path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(level.enemy_spawns, spawns);
+    assert_eq!(level.tiles.width(), 6);
+}
+
+#[test]
+fn levels_without_spawns_load_with_none() {
+    let level = load("level_0").unwrap();
+
+    assert!(level.enemy_spawns.is_empty());
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::*;
+use dungeon_game::enemy::flow_field::*;
+use dungeon_game::enemy::navigation::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+#[test]
+fn recomputes_only_when_target_changes_tile() {
+    let tiles = load("level_0").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+    let mut flow_field = FlowField::new(NeighborMode::Four);
+
+    assert!(flow_field.update(&nav_grid, vec2(290.0, 290.0)));
+    assert!(!flow_field.update(&nav_grid, vec2(300.0, 310.0)));
+    assert!(flow_field.update(&nav_grid, vec2(330.0, 290.0)));
+    assert_eq!(flow_field.goal(), Some((10, 9)));
+}
+
+#[test]
+fn distances_match_a_star_costs() {
+    let tiles = load("coolest_level").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+    let goal = grid_to_world(1, 6);
+
+    for mode in [
+        NeighborMode::Four,
+        NeighborMode::Eight { cut_corners: false },
+    ] {
+        let mut flow_field = FlowField::new(mode);
+        flow_field.update(&nav_grid, goal);
+
+        for start in [
+            grid_to_world(18, 18),
+            grid_to_world(9, 11),
+            grid_to_world(5, 5),
+        ] {
+            let path = nav_grid
+                .find_path(start, goal, mode, MAX_PATH_EXPANSIONS)
+                .unwrap();
+
+            assert_eq!(
+                flow_field.distance(start),
+                Some(path_cost(mode, start, &path))
+            );
+        }
+    }
+}
+
+#[test]
+fn following_steps_reaches_the_goal() {
+    let tiles = load("gigachad").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+    let mode = NeighborMode::Eight { cut_corners: false };
+
+    let mut flow_field = FlowField::new(mode);
+    flow_field.update(&nav_grid, grid_to_world(1, 1));
+
+    let mut pos = grid_to_world(48, 48);
+    let mut steps = 0;
+
+    while let Some(next) = flow_field.next_step(pos) {
+        assert!(flow_field.distance(next) < flow_field.distance(pos));
+        assert!(
+            nav_grid.can_traverse(pos, next),
+            "{:?} {:?}",
+            world_to_grid(pos),
+            world_to_grid(next)
+        );
+        pos = next;
+        steps += 1;
+        assert!(steps < 2500);
+    }
+
+    assert!(
+        flow_field.targets(pos),
+        "{:?} {:?}",
+        pos,
+        flow_field.distance(pos)
+    );
+    assert_eq!(flow_field.distance(pos), Some(0));
+}
+
+#[test]
+fn walls_and_enclosed_cells_have_no_flow() {
+    let mut tiles = TileMap::new(7, 7);
+    for (col, row) in [(3, 2), (2, 3), (4, 3), (3, 4)] {
+        tiles.set_wall(col, row, true);
+    }
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let mut flow_field = FlowField::new(NeighborMode::Eight { cut_corners: false });
+    flow_field.update(&nav_grid, grid_to_world(0, 0));
+
+    assert_eq!(flow_field.distance(grid_to_world(3, 3)), None);
+    assert_eq!(flow_field.next_step(grid_to_world(3, 3)), None);
+    assert_eq!(flow_field.distance(grid_to_world(3, 2)), None);
+    assert!(flow_field.next_step(grid_to_world(6, 6)).is_some());
+}
@@ -0,0 +1,115 @@
+use dungeon_game::input::gamepad::*;
+use dungeon_game::input::*;
+use dungeon_game::player::*;
+use dungeon_game::tile::*;
+use dungeon_game::timestep::*;
+
+use macroquad::prelude::*;
+
+fn pad(pressed: &[PadButton], down: &[PadButton]) -> GamepadState {
+    GamepadState {
+        connected: true,
+        pressed: pressed.to_vec(),
+        down: down.to_vec(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn deadzone_removes_drift_and_rescales() {
+    assert_eq!(apply_deadzone(vec2(0.1, 0.1), STICK_DEADZONE), Vec2::ZERO);
+    assert!((apply_deadzone(vec2(1.0, 0.0), STICK_DEADZONE).x - 1.0).abs() < 1e-5);
+
+    let half = apply_deadzone(vec2(0.0, 0.6), STICK_DEADZONE);
+    assert!((half.y - 0.5).abs() < 1e-5);
+    assert_eq!(half.x, 0.0);
+}
+
+#[test]
+fn pad_buttons_are_bindable() {
+    assert_eq!(
+        Binding::parse("PadWest"),
+        Some(Binding::new(InputSource::Gamepad(PadButton::West)))
+    );
+    assert_eq!(
+        Binding::parse("padrighttrigger2").unwrap().name(),
+        "PadRightTrigger2"
+    );
+}
+
+#[test]
+fn default_pad_bindings_trigger_combat_actions() {
+    let input_map = InputMap::default();
+
+    let attack = pad(&[PadButton::RightTrigger2], &[PadButton::RightTrigger2]);
+    assert!(input_map.gamepad_pressed(Action::Attack, &attack));
+    assert!(!input_map.gamepad_pressed(Action::Block, &attack));
+
+    let block = pad(&[PadButton::LeftTrigger2], &[]);
+    assert!(input_map.gamepad_pressed(Action::Block, &block));
+}
+
+#[test]
+fn stick_movement_wins_over_dpad_when_larger() {
+    let input_map = InputMap::default();
+
+    let dpad = pad(&[], &[PadButton::DPadUp, PadButton::DPadRight]);
+    assert_eq!(input_map.gamepad_movement(&dpad), vec2(1.0, -1.0));
+
+    let mut stick = pad(&[], &[]);
+    stick.left_stick = vec2(0.0, 0.5);
+    assert_eq!(input_map.gamepad_movement(&stick), vec2(0.0, 0.5));
+}
+
+#[test]
+fn right_stick_aim_holds_until_mouse_moves() {
+    let mut aim = AimTracker::new();
+    let origin = vec2(100.0, 100.0);
+    let mouse = vec2(300.0, 300.0);
+
+    assert_eq!(
+        aim.aim(mouse, vec2(200.0, 100.0), origin, Vec2::ZERO),
+        vec2(1.0, 0.0)
+    );
+
+    assert_eq!(
+        aim.aim(mouse, vec2(200.0, 100.0), origin, vec2(0.0, -0.8)),
+        vec2(0.0, -1.0)
+    );
+    assert!(aim.using_gamepad());
+
+    assert_eq!(
+        aim.aim(mouse, vec2(200.0, 100.0), origin, Vec2::ZERO),
+        Vec2::ZERO
+    );
+
+    assert_eq!(
+        aim.aim(
+            mouse + vec2(5.0, 0.0),
+            vec2(100.0, 200.0),
+            origin,
+            Vec2::ZERO
+        ),
+        vec2(0.0, 1.0)
+    );
+    assert!(!aim.using_gamepad());
+}
+
+#[test]
+fn analog_movement_scales_player_speed() {
+    let mut tiles = TileMap::new(20, 5);
+    tiles.fill(TileType::Floor(1), empty_source());
+
+    let mut player = Player::new(64.0, 64.0, 32.0, 32.0, 100.0);
+    let input = InputSnapshot {
+        movement: vec2(0.5, 0.0),
+        ..Default::default()
+    };
+
+    for _ in 0..60 {
+        player.update(&input, &tiles, &mut [], FIXED_DT);
+    }
+
+    assert!((player.pos().x - 114.0).abs() < 0.1);
+    assert_eq!(player.aim(), vec2(1.0, 0.0));
+}
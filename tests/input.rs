@@ -0,0 +1,95 @@
+use dungeon_game::input::*;
+
+use macroquad::prelude::*;
+
+#[test]
+fn bindings_parse_keys_mouse_and_modifiers() {
+    assert_eq!(
+        Binding::parse("Ctrl+Tab"),
+        Some(Binding::new(InputSource::Key(KeyCode::Tab)).with_ctrl())
+    );
+    assert_eq!(
+        Binding::parse("shift + mouseleft"),
+        Some(Binding::new(InputSource::Mouse(MouseButton::Left)).with_shift())
+    );
+    assert_eq!(Binding::parse("Hyper+W"), None);
+    assert_eq!(Binding::parse("NotAKey"), None);
+}
+
+#[test]
+fn unmodified_bindings_ignore_modified_presses() {
+    let plain = Binding::parse("C").unwrap();
+    let ctrl = Binding::parse("Ctrl+C").unwrap();
+
+    assert!(plain.accepts_modifiers(false, false, false));
+    assert!(!plain.accepts_modifiers(true, false, false));
+    assert!(!plain.accepts_modifiers(false, true, false));
+    assert!(!plain.accepts_modifiers(false, false, true));
+    assert!(ctrl.accepts_modifiers(true, false, false));
+    assert!(!ctrl.accepts_modifiers(false, false, false));
+}
+
+#[test]
+fn binding_names_round_trip() {
+    for text in ["W", "Ctrl+Tab", "Ctrl+Shift+Alt+F5", "MouseRight"] {
+        assert_eq!(Binding::parse(text).unwrap().name(), text);
+    }
+}
+
+#[test]
+fn every_action_has_a_default_binding() {
+    let input_map = InputMap::default();
+
+    for action in Action::ALL.iter() {
+        assert!(!input_map.bindings(*action).is_empty(), "{:?}", action);
+    }
+}
+
+#[test]
+fn config_overrides_only_listed_actions() {
+    let input_map =
+        InputMap::from_json(r#"{ "MoveUp": ["Up", "K"], "Attack": ["Space"] }"#).unwrap();
+
+    assert_eq!(
+        input_map.bindings(Action::MoveUp),
+        &[
+            Binding::new(InputSource::Key(KeyCode::Up)),
+            Binding::new(InputSource::Key(KeyCode::K)),
+        ]
+    );
+    assert_eq!(
+        input_map.bindings(Action::Attack),
+        &[Binding::new(InputSource::Key(KeyCode::Space))]
+    );
+    assert_eq!(
+        input_map.bindings(Action::MoveDown),
+        InputMap::default().bindings(Action::MoveDown)
+    );
+}
+
+#[test]
+fn config_round_trips_through_json() {
+    let mut input_map = InputMap::default();
+    input_map.bind(
+        Action::Block,
+        Binding::new(InputSource::Key(KeyCode::LeftShift)),
+    );
+
+    let json = input_map.to_json().unwrap();
+
+    assert_eq!(InputMap::from_json(&json).unwrap(), input_map);
+}
+
+#[test]
+fn shipped_config_matches_defaults() {
+    assert_eq!(InputMap::load(INPUT_CONFIG_PATH), InputMap::default());
+}
+
+#[test]
+fn invalid_config_falls_back_to_defaults() {
+    assert!(InputMap::from_json("{ \"Jump\": [\"Space\"] }").is_err());
+    assert_eq!(
+        InputMap::load("res/config/does_not_exist.json"),
+        InputMap::default()
+    );
+}
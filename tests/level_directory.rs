@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use dungeon_game::editor::tilemap_editor::*;
+use dungeon_game::replay::*;
+use dungeon_game::serialization::directory::*;
+use dungeon_game::serialization::*;
+
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn shipped_levels_are_listed() {
+    let names: Vec<String> = list_levels(LEVEL_DIR)
+        .unwrap()
+        .iter()
+        .map(|entry| entry.name().to_string())
+        .collect();
+
+    for name in ["ai_test", "coolest_level", "gigachad", "level_0"] {
+        assert!(names.contains(&name.to_string()), "{} missing", name);
+    }
+}
+
+#[test]
+fn levels_save_and_load_in_a_configured_directory() {
+    let dir = scratch_dir("configured");
+    let level = LevelData::new("custom", spawn_room(4, 4));
+
+    assert!(!level_exists(&dir, "custom"));
+    save_to(&dir, &level, "custom").unwrap();
+    save_to(&dir, &level, "custom.msgpack").unwrap();
+    std::fs::write(format!("{}/notes.txt", dir), "not a level").unwrap();
+
+    assert!(level_exists(&dir, "custom"));
+    assert!(!level_exists(LEVEL_DIR, "custom"));
+    assert_eq!(load_from(&dir, "custom").unwrap().tiles.width(), 4);
+    assert_eq!(load_from(&dir, "custom.msgpack").unwrap().name, "custom");
+
+    let entries = list_levels(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name()).collect();
+    assert_eq!(names, vec!["custom", "custom.msgpack"]);
+    assert!(entries.iter().all(|entry| entry.size() > 0));
+    assert!(entries.iter().all(|entry| entry.modified().is_some()));
+}
+
+#[test]
+fn missing_directories_are_reported() {
+    assert!(list_levels("res/levels/does_not_exist").is_err());
+}
+
+#[test]
+fn entry_names_load_with_the_right_format() {
+    assert_eq!(entry_name("level_0.json"), Some("level_0".to_string()));
+    assert_eq!(entry_name("big.msgpack"), Some("big.msgpack".to_string()));
+    assert_eq!(entry_name("readme.txt"), None);
+}
+
+#[test]
+fn entry_labels_are_human_readable() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let entry = |size, age| LevelEntry::new("level", size, Some(now - Duration::from_secs(age)));
+
+    assert_eq!(entry(512, 5).size_label(), "512 B");
+    assert_eq!(entry(12_800, 5).size_label(), "12.5 KB");
+    assert_eq!(entry(3 * 1024 * 1024, 5).size_label(), "3.0 MB");
+
+    assert_eq!(entry(1, 5).modified_label(now), "just now");
+    assert_eq!(entry(1, 300).modified_label(now), "5m ago");
+    assert_eq!(entry(1, 7200).modified_label(now), "2h ago");
+    assert_eq!(entry(1, 3 * 86400).modified_label(now), "3d ago");
+    assert_eq!(
+        LevelEntry::new("level", 1, None).modified_label(now),
+        "unknown"
+    );
+}
+
+#[test]
+fn levels_dir_comes_from_the_command_line() {
+    let args = |list: &[&str]| {
+        list.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>()
+    };
+
+    assert_eq!(
+        parse_levels_dir(args(&["--levels-dir", "maps"]).into_iter()),
+        Some("maps".to_string())
+    );
+    assert_eq!(parse_levels_dir(args(&["--seed", "4"]).into_iter()), None);
+}
+
+#[test]
+fn recordings_replay_levels_from_their_directory() {
+    let dir = scratch_dir("recording");
+    save_to(
+        &dir,
+        &LevelData::new("elsewhere", spawn_room(4, 4)),
+        "elsewhere",
+    )
+    .unwrap();
+
+    let recording = Recording::record(&dir, "elsewhere", 3, false).unwrap();
+    let world = recording.build_world();
+    let default_dir = Recording::record(LEVEL_DIR, "elsewhere", 3, false);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(recording.levels_dir(), dir);
+    assert!(world.is_ok());
+    assert!(default_dir.is_err());
+}
+
+#[test]
+fn selecting_an_entry_does_not_change_the_open_level() {
+    let mut editor = TileMapEditor::new(0.0, 0.0, 1.0, 1.0, 16.0, (10, 10), (1.0, 1.0));
+    assert_eq!(editor.current_file(), None);
+
+    editor.open_level("level_0");
+    editor.select_entry("gigachad");
+
+    assert_eq!(editor.filename(), "gigachad");
+    assert_eq!(editor.current_file(), Some("level_0"));
+}
@@ -0,0 +1,300 @@
+mod common;
+
+use common::*;
+use dungeon_game::enemy::patrol::*;
+use dungeon_game::enemy::spawn::*;
+use dungeon_game::enemy::*;
+use dungeon_game::serialization::error::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+#[test]
+fn bare_tile_arrays_migrate_to_current_version() {
+    let json = r#"[
+        {"pos": {"x": 0.0, "y": 0.0}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"Wall": 2}},
+        {"pos": {"x": 64.0, "y": 32.0}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"PlayerSpawn": 3}}
+    ]"#;
+
+    let level = Level::parse(json, "legacy").unwrap();
+
+    assert_eq!(level.version(), LEVEL_VERSION);
+    assert_eq!(level.name(), "legacy");
+    assert_eq!((level.width(), level.height()), (3, 2));
+    assert_eq!(level.tile_size(), TILE_SIZE);
+    assert_eq!(level.atlas(), DEFAULT_ATLAS);
+
+    let data = level.into_data();
+    assert!(data.tiles.is_wall(0, 0));
+    assert_eq!(data.player_spawn(), Some(grid_to_world(2, 1)));
+}
+
+#[test]
+fn unversioned_objects_keep_routes_and_spawns() {
+    let json = r#"{
+        "tiles": [
+            {"pos": {"x": 32.0, "y": 32.0}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"Floor": 1}}
+        ],
+        "patrol_routes": [[{"x": 32.0, "y": 32.0}, {"x": 0.0, "y": 0.0}]],
+        "enemy_spawns": [{"pos": {"x": 32.0, "y": 32.0}, "kind": "Sword", "count": 2}]
+    }"#;
+
+    let data = Level::parse(json, "v1").unwrap().into_data();
+
+    assert_eq!(data.tiles.width(), 2);
+    assert_eq!(data.patrol_routes.len(), 1);
+    assert_eq!(data.patrol_routes[0].waypoints().len(), 2);
+    assert_eq!(
+        data.enemy_spawns,
+        vec![EnemySpawn::new(vec2(32.0, 32.0), EnemyKind::Sword, 2)]
+    );
+}
+
+#[test]
+fn levels_round_trip_through_the_current_format() {
+    let dir = scratch_dir("level_format_round_trip");
+    let path = "round_trip";
+    let mut level = LevelData::new(path, spawn_room(7, 5));
+    level.patrol_routes = vec![PatrolRoute::new(vec![vec2(32.0, 32.0), vec2(96.0, 32.0)])];
+    level.enemy_spawns = vec![EnemySpawn::new(vec2(64.0, 64.0), EnemyKind::Sword, 4)];
+
+    save_to(&dir, &level, path).unwrap();
+    let contents = std::fs::read_to_string(format!("{}/{}.json", dir, path)).unwrap();
+    let loaded = load_from(&dir, path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(contents.contains("\"version\""));
+    assert_eq!(loaded.name, path);
+    assert_eq!((loaded.tiles.width(), loaded.tiles.height()), (7, 5));
+    assert_eq!(loaded.player_spawn(), Some(grid_to_world(1, 1)));
+    assert_eq!(loaded.patrol_routes, level.patrol_routes);
+    assert_eq!(loaded.enemy_spawns, level.enemy_spawns);
+}
+
+#[test]
+fn header_dimensions_size_the_tile_map() {
+    let json = r#"{
+        "version": 2, "name": "sparse", "width": 4, "height": 3, "tile_size": 32.0,
+        "atlas": "res/textures/tilemap_packed.png", "player_spawn": {"x": 96.0, "y": 64.0},
+        "tiles": [
+            {"pos": {"x": 0.0, "y": 0.0}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"Floor": 1}}
+        ]
+    }"#;
+
+    let data = Level::parse(json, "sparse").unwrap().into_data();
+
+    assert_eq!((data.tiles.width(), data.tiles.height()), (4, 3));
+    assert_eq!(data.player_spawn(), Some(grid_to_world(3, 2)));
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let json = format!(
+        r#"{{"version": {}, "name": "future", "width": 0, "height": 0, "tile_size": 32.0, "atlas": "", "player_spawn": null, "tiles": []}}"#,
+        LEVEL_VERSION + 1
+    );
+
+    assert!(Level::parse(&json, "future").is_err());
+}
+
+#[test]
+fn shipped_levels_still_load() {
+    for name in ["level_0", "ai_test", "coolest_level", "gigachad"] {
+        let level = load(name).unwrap();
+
+        assert!(!level.tiles.is_empty(), "{} did not load", name);
+        assert!(level.player_spawn().is_some(), "{} has no spawn", name);
+    }
+}
+
+#[test]
+fn file_extension_selects_the_format() {
+    assert_eq!(LevelFormat::from_path("level_0"), LevelFormat::Json);
+    assert_eq!(LevelFormat::from_path("level_0.json"), LevelFormat::Json);
+    assert_eq!(
+        LevelFormat::from_path("level_0.msgpack"),
+        LevelFormat::MessagePack
+    );
+    assert_eq!(level_path("level_0"), "res/levels/level_0.json");
+    assert_eq!(level_path("level_0.msgpack"), "res/levels/level_0.msgpack");
+    assert_eq!(level_name("level_0.msgpack"), "level_0");
+}
+
+#[test]
+fn binary_levels_match_their_json_source() {
+    let dir = scratch_dir("binary_round_trip");
+    let path = "gigachad.msgpack";
+    let json = load("gigachad").unwrap();
+
+    save_to(&dir, &json, path).unwrap();
+    let bytes = std::fs::metadata(format!("{}/{}", dir, path))
+        .unwrap()
+        .len();
+    let binary = load_from(&dir, path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let text = std::fs::metadata(level_path("gigachad")).unwrap().len();
+    assert!(bytes < text);
+    assert_eq!(binary.name, "gigachad");
+    assert_eq!(
+        serde_json::to_value(binary.tiles.tiles()).unwrap(),
+        serde_json::to_value(json.tiles.tiles()).unwrap()
+    );
+    assert_eq!(binary.player_spawn(), json.player_spawn());
+}
+
+#[test]
+fn corrupt_binary_levels_are_rejected() {
+    assert!(Level::from_bytes(&[0xc1, 0x00, 0x17]).is_err());
+}
+
+#[test]
+fn missing_levels_report_io_errors() {
+    match load("level_that_does_not_exist") {
+        Err(LevelError::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::NotFound),
+        other => panic!("expected io error, got {:?}", other.map(|level| level.name)),
+    }
+}
+
+#[test]
+fn saving_into_a_missing_directory_is_an_io_error() {
+    let level = LevelData::new("nowhere", spawn_room(7, 5));
+
+    assert!(matches!(
+        save(&level, "missing_directory/nowhere"),
+        Err(LevelError::Io(_))
+    ));
+}
+
+#[test]
+fn load_errors_are_categorised() {
+    assert!(matches!(
+        Level::parse("{ not json", "broken"),
+        Err(LevelError::Parse(_))
+    ));
+    assert!(matches!(
+        Level::parse("42", "number"),
+        Err(LevelError::Parse(_))
+    ));
+
+    let future = format!(r#"{{"version": {}}}"#, LEVEL_VERSION + 1);
+    assert!(matches!(
+        Level::parse(&future, "future"),
+        Err(LevelError::UnsupportedVersion(version)) if version == LEVEL_VERSION + 1
+    ));
+
+    let short = r#"{
+        "version": 3, "name": "short", "width": 2, "height": 2, "tile_size": 32.0,
+        "atlas": "", "player_spawn": null,
+        "tiles": {"atlas_tile_size": 16.0, "atlas_columns": 12,
+                  "atlas": {"Raw": [0]}, "collision": {"Raw": [1, 1, 1, 1]}}
+    }"#;
+    assert!(matches!(
+        Level::parse(short, "short"),
+        Err(LevelError::Validation(_))
+    ));
+}
+
+#[test]
+fn mismatched_tile_sizes_are_rejected() {
+    let json = r#"{
+        "version": 3, "name": "big", "width": 1, "height": 1, "tile_size": 64.0,
+        "atlas": "", "player_spawn": null,
+        "tiles": {"atlas_tile_size": 16.0, "atlas_columns": 12,
+                  "atlas": {"Raw": [0]}, "collision": {"Raw": [1]}}
+    }"#;
+
+    match Level::parse(json, "big") {
+        Err(LevelError::Validation(message)) => assert!(message.contains("64")),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+fn compact_level(width: i32, height: i32) -> String {
+    format!(
+        r#"{{
+            "version": 3, "name": "sized", "width": {}, "height": {}, "tile_size": 32.0,
+            "atlas": "", "player_spawn": null,
+            "tiles": {{"atlas_tile_size": 16.0, "atlas_columns": 12,
+                      "atlas": {{"Rle": [[0, 1]]}}, "collision": {{"Rle": [[1, 1]]}}}}
+        }}"#,
+        width, height
+    )
+}
+
+#[test]
+fn non_positive_or_oversized_dimensions_are_rejected() {
+    assert!(Level::parse(&compact_level(1, 1), "sized").is_ok());
+
+    for (width, height) in [
+        (0, 1),
+        (1, -4),
+        (MAX_MAP_SIZE + 1, 1),
+        (1, MAX_MAP_SIZE + 1),
+        (i32::MAX, i32::MAX),
+    ] {
+        assert!(
+            matches!(
+                Level::parse(&compact_level(width, height), "sized"),
+                Err(LevelError::Validation(_))
+            ),
+            "{}x{} was accepted",
+            width,
+            height
+        );
+    }
+}
+
+#[test]
+fn explicit_tiles_outside_the_map_are_rejected() {
+    let json = r#"{
+        "version": 4, "name": "outside", "width": 2, "height": 2, "tile_size": 32.0,
+        "atlas": "", "player_spawn": null,
+        "tiles": [
+            {"pos": {"x": 0.0, "y": 0.0}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"Floor": 1}},
+            {"pos": {"x": 96.0, "y": 0.0}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"Floor": 1}}
+        ]
+    }"#;
+
+    match Level::parse(json, "outside") {
+        Err(LevelError::Validation(message)) => assert!(message.contains("(3, 0)")),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn far_off_legacy_tiles_are_rejected_before_allocating() {
+    let json = r#"[
+        {"pos": {"x": 0.0, "y": 0.0}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"Floor": 1}},
+        {"pos": {"x": 1.0e30, "y": 1.0e12}, "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}, "tile_type": {"Floor": 1}}
+    ]"#;
+
+    assert!(matches!(
+        Level::parse(json, "far"),
+        Err(LevelError::Validation(_))
+    ));
+}
+
+#[test]
+fn patrol_routes_round_trip_through_json_and_msgpack() {
+    let dir = scratch_dir("patrol_round_trip");
+    let mut level = LevelData::new("patrols", spawn_room(8, 8));
+    level.patrol_routes = vec![
+        PatrolRoute::new(vec![
+            grid_to_world(1, 2),
+            grid_to_world(5, 2),
+            grid_to_world(5, 6),
+        ]),
+        PatrolRoute::new(vec![grid_to_world(3, 3)]),
+    ];
+
+    for path in ["patrols.json", "patrols.msgpack"] {
+        save_to(&dir, &level, path).unwrap();
+        let loaded = load_from(&dir, path).unwrap();
+
+        assert_eq!(loaded.patrol_routes, level.patrol_routes, "{}", path);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
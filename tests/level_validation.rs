@@ -0,0 +1,182 @@
+mod common;
+
+use common::*;
+use dungeon_game::editor::tilemap_editor::*;
+use dungeon_game::serialization::validation::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+const ATLAS_SIZE: Vec2 = Vec2::new(192.0, 176.0);
+
+fn room(width: i32, height: i32) -> TileMap {
+    let mut tiles = spawn_room(width, height);
+    for col in 0..width {
+        tiles.set_wall(col, 0, true);
+        tiles.set_wall(col, height - 1, true);
+    }
+    for row in 0..height {
+        tiles.set_wall(0, row, true);
+        tiles.set_wall(width - 1, row, true);
+    }
+
+    tiles
+}
+
+#[test]
+fn shipped_levels_have_no_errors() {
+    for name in ["level_0", "ai_test", "coolest_level", "gigachad"] {
+        let level = load(name).unwrap();
+        let diagnostics = validate_level(&level.tiles, ATLAS_SIZE);
+
+        assert!(!has_errors(&diagnostics), "{}: {:?}", name, diagnostics);
+    }
+}
+
+#[test]
+fn a_clean_room_has_no_diagnostics() {
+    assert!(validate_level(&room(6, 6), ATLAS_SIZE).is_empty());
+}
+
+#[test]
+fn missing_and_duplicate_spawns_are_errors() {
+    let mut tiles = room(6, 6);
+    tiles.set_type(1, 1, TileType::Floor(1));
+    assert_eq!(
+        validate_level(&tiles, ATLAS_SIZE),
+        vec![Diagnostic::NoPlayerSpawn]
+    );
+
+    let mut tiles = room(6, 6);
+    tiles.set_type(4, 4, TileType::PlayerSpawn(3));
+    let diagnostics = validate_level(&tiles, ATLAS_SIZE);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::MultiplePlayerSpawns(vec![(1, 1), (4, 4)])]
+    );
+    assert!(has_errors(&diagnostics));
+}
+
+#[test]
+fn solid_spawn_cells_are_errors() {
+    let mut tiles = room(6, 6);
+    tiles.set_wall(1, 1, true);
+
+    let diagnostics = validate_level(&tiles, ATLAS_SIZE);
+
+    assert!(diagnostics.contains(&Diagnostic::SolidPlayerSpawn((1, 1))));
+    assert!(has_errors(&diagnostics));
+}
+
+#[test]
+fn placing_the_spawn_clears_the_solid_flag() {
+    let mut tiles = room(6, 6);
+    tiles.set_type(1, 1, TileType::Floor(1));
+    tiles.set_wall(3, 3, true);
+
+    let mut editor = TileMapEditor::new(0.0, 0.0, 1.0, 1.0, 16.0, (10, 10), (1.0, 1.0));
+    editor.tiles = tiles;
+    editor.place_player_spawn(grid_to_world(3, 3));
+
+    assert!(matches!(
+        editor.tiles.get(3, 3).unwrap().tile_type(),
+        TileType::PlayerSpawn(_)
+    ));
+    assert!(!editor.tiles.is_wall(3, 3));
+
+    editor.validate();
+    assert!(!editor.play_blocked());
+}
+
+#[test]
+fn removing_the_spawn_restores_the_ground_type() {
+    let mut tiles = room(6, 6);
+    tiles.set_type(1, 1, TileType::Floor(1));
+    tiles.set_layer_source(Layer::Ground, 1, 1, empty_source());
+
+    let mut editor = TileMapEditor::new(0.0, 0.0, 1.0, 1.0, 16.0, (10, 10), (1.0, 1.0));
+    editor.tiles = tiles;
+    editor.place_player_spawn(grid_to_world(1, 1));
+    editor.remove_player_spawn();
+    assert_eq!(
+        editor.tiles.get(1, 1).unwrap().tile_type(),
+        TileType::Empty(0)
+    );
+
+    editor.place_player_spawn(grid_to_world(2, 2));
+    editor.remove_player_spawn();
+    assert_eq!(
+        editor.tiles.get(2, 2).unwrap().tile_type(),
+        TileType::Floor(1)
+    );
+}
+
+#[test]
+fn walled_off_floors_are_warnings() {
+    let mut tiles = room(8, 5);
+    for row in 0..5 {
+        tiles.set_wall(3, row, true);
+    }
+
+    let diagnostics = validate_level(&tiles, ATLAS_SIZE);
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::UnreachableFloor {
+            cells: 9,
+            first: (4, 1)
+        }]
+    );
+    assert_eq!(diagnostics[0].severity(), Severity::Warning);
+    assert!(!has_errors(&diagnostics));
+}
+
+#[test]
+fn bad_sources_and_positions_are_reported() {
+    let mut tiles = room(4, 4);
+    tiles.set_layer_source(Layer::Ground, 2, 2, Rect::new(184.0, 0.0, 16.0, 16.0));
+    let shifted = Tile::new(
+        vec2(40.0, 32.0),
+        Rect::new(0.0, 0.0, 16.0, 16.0),
+        TileType::Floor(1),
+    );
+    tiles.set_legacy_tile(1, 2, shifted);
+
+    let diagnostics = validate_level(&tiles, ATLAS_SIZE);
+
+    assert!(diagnostics.contains(&Diagnostic::InvalidAtlasRect {
+        layer: Layer::Ground,
+        cell: (2, 2),
+        source: Rect::new(184.0, 0.0, 16.0, 16.0)
+    }));
+    assert!(diagnostics.contains(&Diagnostic::OffGridPosition {
+        cell: (1, 2),
+        pos: vec2(40.0, 32.0)
+    }));
+    assert!(diagnostics
+        .iter()
+        .all(|diagnostic| !diagnostic.message().is_empty()));
+}
+
+#[test]
+fn editor_revalidates_only_after_changes() {
+    let mut editor = TileMapEditor::new(0.0, 0.0, 1.0, 1.0, 16.0, (10, 10), (1.0, 1.0));
+    assert!(!editor.needs_validation());
+    assert!(editor.diagnostics().is_empty());
+
+    editor.open_level("level_0");
+    assert!(editor.needs_validation());
+
+    editor.validate();
+    assert!(!editor.needs_validation());
+}
+
+#[test]
+fn blocked_play_is_reported_by_the_editor() {
+    let mut editor = TileMapEditor::new(0.0, 0.0, 1.0, 1.0, 16.0, (10, 10), (1.0, 1.0));
+
+    assert!(!editor.ready_to_play());
+    assert!(editor.play_blocked());
+    assert!(editor.diagnostics().contains(&Diagnostic::NoPlayerSpawn));
+}
@@ -0,0 +1,144 @@
+use dungeon_game::enemy::patrol::*;
+use dungeon_game::enemy::spawn::*;
+use dungeon_game::enemy::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+use dungeon_game::utility::*;
+
+use macroquad::prelude::*;
+
+fn numbered(width: i32, height: i32) -> TileMap {
+    let mut tiles = TileMap::new(width, height);
+    tiles.fill(TileType::Floor(1), empty_source());
+    for row in 0..height {
+        for col in 0..width {
+            let index = row * width + col;
+            tiles.set_layer_source(
+                Layer::Ground,
+                col,
+                row,
+                Rect::new(index as f32 * 16.0, 0.0, 16.0, 16.0),
+            );
+        }
+    }
+
+    tiles
+}
+
+fn source_x(tiles: &TileMap, col: i32, row: i32) -> f32 {
+    tiles.get(col, row).unwrap().source().x
+}
+
+#[test]
+fn anchors_place_growth_on_the_opposite_side() {
+    assert_eq!(Anchor::TopLeft.offset((4, 4), (8, 6)), (0, 0));
+    assert_eq!(Anchor::Center.offset((4, 4), (8, 6)), (2, 1));
+    assert_eq!(Anchor::BottomRight.offset((4, 4), (8, 6)), (4, 2));
+    assert_eq!(Anchor::Right.offset((6, 6), (2, 2)), (-4, -2));
+}
+
+#[test]
+fn growing_keeps_tiles_at_the_anchor() {
+    let tiles = numbered(2, 2);
+
+    let grown = tiles.resized(4, 4, Anchor::BottomRight);
+
+    assert_eq!((grown.width(), grown.height()), (4, 4));
+    assert_eq!(source_x(&grown, 2, 2), 0.0);
+    assert_eq!(source_x(&grown, 3, 3), 48.0);
+    assert_eq!(grown.get(2, 2).unwrap().pos(), grid_to_world(2, 2));
+    assert!(matches!(
+        grown.get(0, 0).unwrap().tile_type(),
+        TileType::Empty(_)
+    ));
+}
+
+#[test]
+fn cropping_from_the_center_keeps_the_middle() {
+    let tiles = numbered(4, 4);
+
+    let cropped = tiles.resized(2, 2, Anchor::Center);
+
+    assert_eq!(source_x(&cropped, 0, 0), 5.0 * 16.0);
+    assert_eq!(source_x(&cropped, 1, 1), 10.0 * 16.0);
+}
+
+#[test]
+fn resizing_shifts_and_drops_entities() {
+    let mut level = LevelData::new("entities", numbered(4, 4));
+    level.enemy_spawns = vec![
+        EnemySpawn::new(grid_to_world(0, 0), EnemyKind::Sword, 1),
+        EnemySpawn::new(grid_to_world(3, 3), EnemyKind::Sword, 2),
+    ];
+    level.patrol_routes = vec![
+        PatrolRoute::new(vec![grid_to_world(0, 0), grid_to_world(3, 3)]),
+        PatrolRoute::new(vec![grid_to_world(0, 1)]),
+    ];
+
+    let (resized, _) = resize_level(&level, 3, 3, Anchor::BottomRight);
+
+    assert_eq!(
+        resized.enemy_spawns,
+        vec![EnemySpawn::new(grid_to_world(2, 2), EnemyKind::Sword, 2)]
+    );
+    assert_eq!(resized.patrol_routes.len(), 1);
+    assert_eq!(resized.patrol_routes[0].waypoints(), &[grid_to_world(2, 2)]);
+}
+
+#[test]
+fn a_cropped_player_spawn_moves_back_inside() {
+    let mut tiles = numbered(6, 6);
+    tiles.set_type(5, 5, TileType::PlayerSpawn(3));
+    let level = LevelData::new("spawn", tiles);
+
+    let (resized, moved) = resize_level(&level, 3, 3, Anchor::TopLeft);
+
+    assert_eq!(moved, SpawnMove::Moved(2, 2));
+    assert_eq!(resized.player_spawn(), Some(grid_to_world(2, 2)));
+}
+
+#[test]
+fn a_cropped_player_spawn_skips_walls_at_the_edge() {
+    let mut tiles = numbered(6, 6);
+    tiles.set_type(5, 5, TileType::PlayerSpawn(3));
+    for i in 0..3 {
+        tiles.set_wall(i, 2, true);
+        tiles.set_wall(2, i, true);
+    }
+    let level = LevelData::new("spawn", tiles);
+
+    let (resized, moved) = resize_level(&level, 3, 3, Anchor::TopLeft);
+
+    assert_eq!(moved, SpawnMove::Moved(1, 1));
+    assert_eq!(resized.player_spawn(), Some(grid_to_world(1, 1)));
+    assert!(!resized.tiles.is_wall(1, 1));
+}
+
+#[test]
+fn a_cropped_player_spawn_without_floor_is_reported() {
+    let mut tiles = numbered(6, 6);
+    tiles.set_type(5, 5, TileType::PlayerSpawn(3));
+    for row in 0..3 {
+        for col in 0..3 {
+            tiles.set_wall(col, row, true);
+        }
+    }
+    let level = LevelData::new("spawn", tiles);
+
+    let (resized, moved) = resize_level(&level, 3, 3, Anchor::TopLeft);
+
+    assert_eq!(moved, SpawnMove::NoFloor);
+    assert_eq!(resized.player_spawn(), None);
+}
+
+#[test]
+fn a_kept_player_spawn_is_not_moved() {
+    let mut tiles = numbered(6, 6);
+    tiles.set_type(1, 1, TileType::PlayerSpawn(3));
+    let level = LevelData::new("spawn", tiles);
+
+    let (resized, moved) = resize_level(&level, 10, 10, Anchor::Center);
+
+    assert_eq!(moved, SpawnMove::Unchanged);
+    assert_eq!(resized.player_spawn(), Some(grid_to_world(3, 3)));
+}
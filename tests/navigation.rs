@@ -0,0 +1,311 @@
+mod common;
+
+use common::*;
+use dungeon_game::enemy::navigation::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+use std::collections::VecDeque;
+
+fn bfs_distance(nav_grid: &NavGrid, start: (i32, i32), goal: (i32, i32)) -> Option<usize> {
+    let mut visited = vec![false; (nav_grid.width() * nav_grid.height()) as usize];
+    let mut frontier = VecDeque::new();
+
+    visited[(start.1 * nav_grid.width() + start.0) as usize] = true;
+    frontier.push_back((start, 0));
+
+    while let Some((cell, distance)) = frontier.pop_front() {
+        if cell == goal {
+            return Some(distance);
+        }
+
+        for next in nav_grid.neighbors(cell.0, cell.1, NeighborMode::Four) {
+            let index = (next.1 * nav_grid.width() + next.0) as usize;
+            if !visited[index] {
+                visited[index] = true;
+                frontier.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    None
+}
+
+fn assert_valid_path(nav_grid: &NavGrid, start: Vec2, path: &Path) {
+    let mut previous = world_to_grid(start);
+
+    for waypoint in path.waypoints().iter() {
+        let (col, row) = world_to_grid(*waypoint);
+        assert!(nav_grid.is_walkable(col, row), "waypoint on wall");
+        assert_eq!((col - previous.0).abs() + (row - previous.1).abs(), 1);
+        previous = (col, row);
+    }
+}
+
+#[test]
+fn finds_shortest_path_out_of_spawn_room() {
+    let tiles = load("level_0").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let start = grid_to_world(9, 9);
+    let goal = grid_to_world(1, 1);
+
+    let path = nav_grid
+        .find_path(start, goal, NeighborMode::Four, MAX_PATH_EXPANSIONS)
+        .expect("level_0 spawn room should connect to the corner");
+
+    assert_valid_path(&nav_grid, start, &path);
+    assert_eq!(path.waypoints().back(), Some(&goal));
+    assert_eq!(Some(path.len()), bfs_distance(&nav_grid, (9, 9), (1, 1)));
+}
+
+#[test]
+fn matches_bfs_distance_on_large_level() {
+    let tiles = load("gigachad").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let start = grid_to_world(1, 1);
+    let goal = grid_to_world(48, 48);
+
+    let path = nav_grid
+        .find_path(start, goal, NeighborMode::Four, MAX_PATH_EXPANSIONS)
+        .expect("gigachad corners should be connected");
+
+    assert_valid_path(&nav_grid, start, &path);
+    assert_eq!(Some(path.len()), bfs_distance(&nav_grid, (1, 1), (48, 48)));
+}
+
+#[test]
+fn same_tile_yields_empty_path() {
+    let tiles = load("ai_test").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let path = nav_grid
+        .find_path(
+            vec2(40.0, 40.0),
+            vec2(50.0, 50.0),
+            NeighborMode::Four,
+            MAX_PATH_EXPANSIONS,
+        )
+        .unwrap();
+
+    assert!(path.is_empty());
+}
+
+#[test]
+fn wall_goal_is_unreachable() {
+    let tiles = load("ai_test").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let path = nav_grid.find_path(
+        grid_to_world(1, 3),
+        grid_to_world(0, 0),
+        NeighborMode::Four,
+        MAX_PATH_EXPANSIONS,
+    );
+
+    assert!(path.is_none());
+}
+
+#[test]
+fn enclosed_goal_is_unreachable() {
+    let mut tiles = TileMap::new(7, 7);
+    for (col, row) in [(3, 2), (2, 3), (4, 3), (3, 4)] {
+        tiles.set_wall(col, row, true);
+    }
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let path = nav_grid.find_path(
+        grid_to_world(0, 0),
+        grid_to_world(3, 3),
+        NeighborMode::Four,
+        MAX_PATH_EXPANSIONS,
+    );
+
+    assert!(path.is_none());
+}
+
+#[test]
+fn expansion_budget_limits_search() {
+    let tiles = load("gigachad").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let start = grid_to_world(1, 1);
+    let goal = grid_to_world(48, 48);
+
+    assert!(nav_grid
+        .find_path(start, goal, NeighborMode::Four, 10)
+        .is_none());
+    assert!(nav_grid
+        .find_path(start, goal, NeighborMode::Four, MAX_PATH_EXPANSIONS)
+        .is_some());
+}
+
+#[test]
+fn diagonal_mode_crosses_open_room_directly() {
+    let tiles = TileMap::new(8, 8);
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+    let mode = NeighborMode::Eight { cut_corners: false };
+
+    let start = grid_to_world(0, 0);
+    let goal = grid_to_world(5, 3);
+
+    let path = nav_grid
+        .find_path(start, goal, mode, MAX_PATH_EXPANSIONS)
+        .unwrap();
+
+    assert_eq!(path.len(), 5);
+    assert_eq!(
+        path_cost(mode, start, &path),
+        mode.heuristic((0, 0), (5, 3))
+    );
+}
+
+#[test]
+fn corner_cutting_is_configurable() {
+    let mut tiles = TileMap::new(3, 3);
+    tiles.set_wall(1, 0, true);
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let strict = nav_grid.neighbors(0, 0, NeighborMode::Eight { cut_corners: false });
+    let relaxed = nav_grid.neighbors(0, 0, NeighborMode::Eight { cut_corners: true });
+
+    assert!(!strict.contains(&(1, 1)));
+    assert!(relaxed.contains(&(1, 1)));
+}
+
+#[test]
+fn diagonal_paths_never_cut_wall_corners() {
+    let tiles = load("gigachad").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+    let mode = NeighborMode::Eight { cut_corners: false };
+
+    let start = grid_to_world(1, 1);
+    let path = nav_grid
+        .find_path(start, grid_to_world(48, 48), mode, MAX_PATH_EXPANSIONS)
+        .unwrap();
+
+    let mut previous = world_to_grid(start);
+    for waypoint in path.waypoints().iter() {
+        let (col, row) = world_to_grid(*waypoint);
+        assert!(nav_grid.is_walkable(col, row));
+        assert!((col - previous.0).abs() <= 1 && (row - previous.1).abs() <= 1);
+
+        if col != previous.0 && row != previous.1 {
+            assert!(nav_grid.is_walkable(col, previous.1));
+            assert!(nav_grid.is_walkable(previous.0, row));
+        }
+
+        previous = (col, row);
+    }
+
+    let four = nav_grid
+        .find_path(
+            start,
+            grid_to_world(48, 48),
+            NeighborMode::Four,
+            MAX_PATH_EXPANSIONS,
+        )
+        .unwrap();
+    assert!(path_cost(mode, start, &path) < path_cost(NeighborMode::Four, start, &four));
+}
+
+fn assert_never_crosses_wall(nav_grid: &NavGrid, start: Vec2, path: &Path) {
+    let mut previous = start;
+
+    for waypoint in path.waypoints().iter() {
+        let distance = previous.distance(*waypoint);
+        let samples = distance.ceil() as i32 * 2;
+
+        for sample in 0..=samples {
+            let point = previous.lerp(*waypoint, sample as f32 / samples.max(1) as f32);
+
+            for corner in [
+                vec2(0.0, 0.0),
+                vec2(2.0, 0.0),
+                vec2(0.0, 2.0),
+                vec2(2.0, 2.0),
+            ] {
+                let probe = point + corner * AGENT_HALF_EXTENT;
+                let (col, row) = world_to_grid(probe);
+                assert!(
+                    nav_grid.is_walkable(col, row),
+                    "segment {:?} -> {:?} crosses wall at {:?}",
+                    previous,
+                    waypoint,
+                    (col, row)
+                );
+            }
+        }
+
+        previous = *waypoint;
+    }
+}
+
+#[test]
+fn raycast_stops_at_first_wall() {
+    let tiles = load("ai_test").unwrap().tiles;
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    let hit = nav_grid.raycast(vec2(48.0, 48.0), vec2(280.0, 48.0));
+
+    assert_eq!(hit, Some((6, 1)));
+    assert!(nav_grid.line_of_sight(vec2(48.0, 48.0), vec2(48.0, 240.0)));
+}
+
+#[test]
+fn raycast_through_corner_checks_both_sides() {
+    let mut tiles = TileMap::new(3, 3);
+    tiles.set_wall(1, 0, true);
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    assert!(!nav_grid.line_of_sight(vec2(16.0, 16.0), vec2(80.0, 80.0)));
+}
+
+#[test]
+fn raycast_ending_on_a_wall_edge_is_clear() {
+    let mut tiles = TileMap::new(3, 1);
+    tiles.set_wall(1, 0, true);
+    let nav_grid = NavGrid::from_tile_map(&tiles);
+
+    assert_eq!(nav_grid.raycast(vec2(16.0, 16.0), vec2(32.0, 16.0)), None);
+    assert_eq!(
+        nav_grid.raycast(vec2(16.0, 16.0), vec2(32.5, 16.0)),
+        Some((1, 0))
+    );
+}
+
+#[test]
+fn smoothing_drops_redundant_waypoints_without_crossing_walls() {
+    for level in ["level_0", "coolest_level", "gigachad"] {
+        let tiles = load(level).unwrap().tiles;
+        let nav_grid = NavGrid::from_tile_map(&tiles);
+
+        let start = grid_to_world(1, 1);
+        let goal = grid_to_world(tiles.width() - 2, tiles.height() - 2);
+
+        for mode in [
+            NeighborMode::Four,
+            NeighborMode::Eight { cut_corners: false },
+        ] {
+            let path = nav_grid
+                .find_path(start, goal, mode, MAX_PATH_EXPANSIONS)
+                .unwrap();
+
+            let smoothed = nav_grid.smooth_path(start, &path);
+            let funneled = nav_grid.funnel_path(start, &path, FUNNEL_TOLERANCE);
+            let combined = nav_grid.smooth_path(start, &funneled);
+
+            for result in [&smoothed, &funneled, &combined] {
+                assert!(result.len() <= path.len());
+                assert_eq!(result.waypoints().back(), Some(&goal));
+                assert_never_crosses_wall(&nav_grid, start, result);
+            }
+
+            assert!(smoothed.len() < path.len());
+            assert!(funneled.len() < path.len());
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use dungeon_game::editor::paint_tool::*;
+use dungeon_game::editor::tilemap_editor::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    cells.sort();
+    cells
+}
+
+fn walled_room() -> TileMap {
+    let mut tiles = TileMap::new(6, 5);
+    for (col, row) in hollow_rect_cells((0, 0), (5, 4)) {
+        tiles.set_wall(col, row, true);
+    }
+    for (col, row) in rect_cells((1, 1), (4, 3)) {
+        tiles.set_type(col, row, TileType::Floor(1));
+        tiles.set_layer_source(Layer::Ground, col, row, Rect::new(16.0, 0.0, 16.0, 16.0));
+    }
+
+    tiles
+}
+
+#[test]
+fn rectangles_cover_the_dragged_area_from_any_corner() {
+    let forward = rect_cells((1, 1), (3, 2));
+    let backward = rect_cells((3, 2), (1, 1));
+
+    assert_eq!(forward.len(), 6);
+    assert_eq!(sorted(forward), sorted(backward));
+}
+
+#[test]
+fn hollow_rectangles_only_keep_the_border() {
+    let cells = hollow_rect_cells((0, 0), (3, 3));
+
+    assert_eq!(cells.len(), 12);
+    assert!(!cells.contains(&(1, 1)));
+    assert!(!cells.contains(&(2, 2)));
+    assert_eq!(hollow_rect_cells((0, 0), (4, 0)).len(), 5);
+}
+
+#[test]
+fn lines_connect_both_endpoints_without_gaps() {
+    let cells = line_cells((0, 0), (5, 2));
+
+    assert_eq!(cells.first(), Some(&(0, 0)));
+    assert_eq!(cells.last(), Some(&(5, 2)));
+    assert_eq!(cells.len(), 6);
+    assert!(cells
+        .windows(2)
+        .all(|pair| (pair[0].0 - pair[1].0).abs() <= 1 && (pair[0].1 - pair[1].1).abs() <= 1));
+    assert_eq!(line_cells((2, 2), (2, 2)), vec![(2, 2)]);
+}
+
+#[test]
+fn ground_fill_stops_at_different_tiles() {
+    let tiles = walled_room();
+
+    let region = flood_fill(&tiles, Layer::Ground, (2, 2));
+
+    assert_eq!(sorted(region), sorted(rect_cells((1, 1), (4, 3))));
+}
+
+#[test]
+fn collision_fill_follows_the_walls() {
+    let tiles = walled_room();
+
+    let region = flood_fill(&tiles, Layer::Collision, (0, 0));
+
+    assert_eq!(sorted(region), sorted(hollow_rect_cells((0, 0), (5, 4))));
+    assert!(flood_fill(&tiles, Layer::Collision, (9, 9)).is_empty());
+}
+
+#[test]
+fn decoration_fill_ignores_the_ground_underneath() {
+    let mut tiles = walled_room();
+    tiles.set_layer_source(Layer::Decoration, 2, 0, Rect::new(32.0, 0.0, 16.0, 16.0));
+
+    let region = flood_fill(&tiles, Layer::Decoration, (0, 0));
+
+    assert_eq!(region.len(), tiles.len() - 1);
+    assert!(!region.contains(&(2, 0)));
+}
+
+#[test]
+fn shape_cells_paint_walls_on_the_collision_layer() {
+    let mut tiles = TileMap::new(4, 4);
+
+    for (col, row) in PaintTool::HollowRect.shape((0, 0), (3, 3)) {
+        let mut cell = tiles.cell(col, row).unwrap();
+        paint_cell(&mut cell, Layer::Collision, Rect::new(0.0, 0.0, 16.0, 16.0));
+        tiles.set_cell(col, row, cell);
+    }
+
+    assert!(tiles.is_wall(0, 0));
+    assert!(tiles.is_wall(3, 2));
+    assert!(!tiles.is_wall(1, 1));
+}
+
+#[test]
+fn collision_paint_leaves_the_player_spawn_walkable() {
+    let mut tiles = walled_room();
+    tiles.set_type(2, 2, TileType::PlayerSpawn(3));
+
+    for (col, row) in PaintTool::Rect.shape((1, 1), (4, 3)) {
+        let mut cell = tiles.cell(col, row).unwrap();
+        paint_cell(&mut cell, Layer::Collision, Rect::new(0.0, 0.0, 16.0, 16.0));
+        tiles.set_cell(col, row, cell);
+    }
+
+    assert!(tiles.is_wall(1, 1));
+    assert!(!tiles.is_wall(2, 2));
+    assert_eq!(
+        tiles.get(2, 2).unwrap().tile_type(),
+        TileType::PlayerSpawn(3)
+    );
+}
+
+#[test]
+fn tools_cycle_through_every_mode() {
+    let mut tool = PaintTool::Brush;
+    let mut seen = Vec::new();
+    for _ in 0..PaintTool::ALL.len() {
+        seen.push(tool);
+        tool = tool.next();
+    }
+
+    assert_eq!(tool, PaintTool::Brush);
+    assert_eq!(seen, PaintTool::ALL.to_vec());
+    assert!(PaintTool::Line.is_shape());
+    assert!(!PaintTool::Fill.is_shape());
+}
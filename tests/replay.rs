@@ -0,0 +1,196 @@
+use dungeon_game::editor::tilemap_editor::*;
+use dungeon_game::input::*;
+use dungeon_game::replay::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+use dungeon_game::timestep::*;
+
+use macroquad::prelude::*;
+
+mod common;
+
+use common::*;
+
+const AMBUSH: &str = "tests/replays/level_0_ambush.json";
+
+fn walk(movement: Vec2) -> InputSnapshot {
+    InputSnapshot {
+        movement,
+        aim: vec2(1.0, 0.0),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn ambush_recording_replays_to_known_outcome() {
+    let recording = Recording::load(AMBUSH).unwrap();
+
+    assert_eq!(recording.level(), "level_0");
+    assert_eq!(recording.seed(), 21);
+    assert_eq!(recording.ticks(), 2400);
+
+    let world = recording.run().unwrap();
+
+    assert_eq!(world.player().health(), 2);
+    assert_eq!(world.enemies().len(), 5);
+}
+
+#[test]
+fn replaying_twice_is_identical() {
+    let recording = Recording::load(AMBUSH).unwrap();
+
+    let first = recording.run().unwrap();
+    let second = recording.run().unwrap();
+
+    assert_eq!(first.player().pos(), second.player().pos());
+    assert_eq!(first.player().health(), second.player().health());
+
+    let positions = |world: &dungeon_game::world::World| {
+        world
+            .enemies()
+            .iter()
+            .map(|enemy| enemy.pos())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(positions(&first), positions(&second));
+}
+
+#[test]
+fn live_run_matches_its_replay() {
+    let mut recording = Recording::record(LEVEL_DIR, "level_0", 42, true).unwrap();
+    let mut world = recording.build_world().unwrap();
+
+    for tick in 0..600 {
+        let input = InputSnapshot {
+            attack: tick % 45 == 0,
+            ..walk(if tick < 70 {
+                vec2(0.0, 1.0)
+            } else {
+                vec2(1.0, 0.0)
+            })
+        };
+
+        world.step(&input, FIXED_DT);
+        recording.push(&input);
+    }
+
+    let replayed = recording.run().unwrap();
+
+    assert_eq!(replayed.player().pos(), world.player().pos());
+    assert_eq!(replayed.player().health(), world.player().health());
+    assert_eq!(replayed.enemies().len(), world.enemies().len());
+}
+
+#[test]
+fn identical_ticks_are_run_length_encoded() {
+    let mut recording = Recording::record(LEVEL_DIR, "level_0", 1, false).unwrap();
+
+    for _ in 0..100 {
+        recording.push(&walk(vec2(0.0, 1.0)));
+    }
+    recording.push(&InputSnapshot {
+        attack: true,
+        ..walk(vec2(0.0, 1.0))
+    });
+
+    assert_eq!(recording.frames().len(), 2);
+    assert_eq!(recording.frames()[0].ticks(), 100);
+    assert_eq!(recording.ticks(), 101);
+    assert!(recording.inputs().last().unwrap().attack);
+}
+
+#[test]
+fn replay_feeds_inputs_in_order() {
+    let mut recording = Recording::record(LEVEL_DIR, "level_0", 1, false).unwrap();
+    recording.push(&walk(vec2(1.0, 0.0)));
+    recording.push(&walk(vec2(0.0, 1.0)));
+
+    let mut replay = Replay::new(recording);
+
+    assert_eq!(replay.next_input().unwrap().movement, vec2(1.0, 0.0));
+    assert_eq!(replay.next_input().unwrap().movement, vec2(0.0, 1.0));
+    assert!(replay.finished());
+    assert!(replay.next_input().is_none());
+
+    replay.restart();
+    assert_eq!(replay.tick(), 0);
+}
+
+#[test]
+fn missing_level_cannot_be_recorded() {
+    assert!(Recording::record(LEVEL_DIR, "does_not_exist", 1, true).is_err());
+}
+
+#[test]
+fn edited_level_refuses_an_old_recording() {
+    let dir = scratch_dir("edited_replay");
+    let mut data = LevelData::new("room", spawn_room(6, 6));
+    save_to(&dir, &data, "room").unwrap();
+
+    let mut recording = Recording::record(&dir, "room", 5, false).unwrap();
+    recording.push(&walk(vec2(1.0, 0.0)));
+    let before = recording.run();
+
+    data.tiles.set_wall(4, 4, true);
+    save_to(&dir, &data, "room").unwrap();
+    let after = recording.run();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(before.is_ok());
+    assert_eq!(
+        after.err().map(|err| err.kind()),
+        Some(std::io::ErrorKind::InvalidData)
+    );
+}
+
+#[test]
+fn content_hash_ignores_the_level_name() {
+    let tiles = spawn_room(6, 6);
+
+    assert_eq!(
+        LevelData::new("a", tiles.clone()).content_hash(),
+        LevelData::new("b", tiles).content_hash()
+    );
+}
+
+#[test]
+fn unsaved_editor_changes_are_detected_before_recording() {
+    let mut editor = TileMapEditor::new(0.0, 0.0, 1.0, 1.0, 16.0, (10, 10), (1.0, 1.0));
+    assert!(editor.has_unsaved_changes());
+
+    editor.open_level("level_0");
+    assert!(!editor.has_unsaved_changes());
+
+    editor.tiles.set_type(0, 0, TileType::Empty(0));
+    assert!(editor.has_unsaved_changes());
+
+    editor.open_level("level_0");
+    assert!(!editor.has_unsaved_changes());
+}
+
+#[test]
+fn content_hash_does_not_depend_on_the_file_format() {
+    let legacy = load("level_0").unwrap();
+    let dir = scratch_dir("hash_format");
+    save_to(&dir, &legacy, "level_0.msgpack").unwrap();
+    let binary = load_from(&dir, "level_0.msgpack").unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(binary.content_hash(), legacy.content_hash());
+}
+
+#[test]
+fn replay_dir_comes_from_the_flag_or_the_user_data_dir() {
+    let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        parse_replay_dir(args(&["--replay-dir", "runs"]).into_iter()),
+        Some("runs".to_string())
+    );
+    assert_eq!(
+        parse_replay_dir(args(&["--replay", "a.json"]).into_iter()),
+        None
+    );
+    assert!(default_replay_dir().ends_with("replays"));
+    assert!(!default_replay_dir().starts_with("res"));
+}
@@ -0,0 +1,82 @@
+use dungeon_game::rng::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+use dungeon_game::utility::*;
+
+use macroquad::prelude::*;
+
+fn spawn_level_0(seed: u64) -> Vec<(Vec2, f32)> {
+    let tiles = load("level_0").unwrap().tiles;
+    let player_pos = tiles
+        .iter()
+        .find(|tile| matches!(tile.tile_type(), TileType::PlayerSpawn(_)))
+        .map(|tile| tile.pos())
+        .unwrap();
+
+    let mut rng = GameRng::new(seed);
+    let mut enemies = Vec::new();
+    spawn_enemy(&tiles, &mut enemies, player_pos, 5, &mut rng);
+
+    enemies
+        .iter()
+        .map(|enemy| (enemy.pos(), enemy.speed()))
+        .collect()
+}
+
+#[test]
+fn same_seed_yields_same_enemy_layout() {
+    let first = spawn_level_0(1234);
+    let second = spawn_level_0(1234);
+
+    assert_eq!(first.len(), 5);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn different_seeds_yield_different_layouts() {
+    assert_ne!(spawn_level_0(1), spawn_level_0(2));
+}
+
+#[test]
+fn reset_replays_the_sequence() {
+    let mut rng = GameRng::new(42);
+    let first: Vec<u32> = (0..8).map(|_| rng.next_u32()).collect();
+
+    rng.reset();
+    let second: Vec<u32> = (0..8).map(|_| rng.next_u32()).collect();
+
+    assert_eq!(rng.seed(), 42);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn gen_range_stays_in_bounds() {
+    let mut rng = GameRng::new(7);
+
+    for _ in 0..1000 {
+        let value = rng.gen_range(90.0, 105.0);
+        assert!((90.0..105.0).contains(&value));
+    }
+}
+
+#[test]
+fn seed_is_parsed_from_arguments() {
+    let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(parse_seed(args(&["--seed", "99"]).into_iter()), Some(99));
+    assert_eq!(parse_seed(args(&["--seed=5"]).into_iter()), Some(5));
+    assert_eq!(parse_seed(args(&["--seed", "abc"]).into_iter()), None);
+    assert_eq!(parse_seed(args(&[]).into_iter()), None);
+}
+
+#[test]
+fn run_seeds_change_between_runs_but_follow_their_source() {
+    let mut seeds = GameRng::new(99);
+    let runs: Vec<u64> = (0..4).map(|_| seeds.next_seed()).collect();
+
+    assert!(runs.windows(2).all(|pair| pair[0] != pair[1]));
+    assert_ne!(spawn_level_0(runs[0]), spawn_level_0(runs[1]));
+
+    let mut again = GameRng::new(99);
+    assert_eq!(again.next_seed(), runs[0]);
+}
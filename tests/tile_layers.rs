@@ -0,0 +1,172 @@
+mod common;
+
+use common::*;
+use dungeon_game::editor::tilemap_editor::*;
+use dungeon_game::enemy::navigation::*;
+use dungeon_game::serialization::error::*;
+use dungeon_game::serialization::validation::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+const ATLAS_SIZE: Vec2 = Vec2::new(192.0, 176.0);
+
+fn layered_room() -> TileMap {
+    let mut tiles = TileMap::new(4, 3);
+    tiles.fill(TileType::Floor(1), Rect::new(16.0, 0.0, 16.0, 16.0));
+    tiles.set_type(0, 0, TileType::PlayerSpawn(3));
+    tiles.set_layer_source(Layer::Decoration, 1, 1, Rect::new(32.0, 16.0, 16.0, 16.0));
+    tiles.set_layer_source(Layer::Overlay, 3, 2, Rect::new(48.0, 32.0, 16.0, 16.0));
+
+    tiles
+}
+
+#[test]
+fn layers_are_ordered_with_overlay_above_actors() {
+    let above: Vec<Layer> = Layer::ALL
+        .iter()
+        .copied()
+        .filter(|layer| layer.above_actors())
+        .collect();
+
+    assert_eq!(above, vec![Layer::Overlay]);
+    assert!(Layer::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(!Layer::Collision.is_visual());
+}
+
+#[test]
+fn layer_sources_are_stored_per_cell() {
+    let tiles = layered_room();
+
+    assert_eq!(
+        tiles.layer_source(Layer::Decoration, 1, 1),
+        Some(Rect::new(32.0, 16.0, 16.0, 16.0))
+    );
+    assert_eq!(tiles.layer_source(Layer::Decoration, 3, 2), None);
+    assert_eq!(tiles.layer_source(Layer::Collision, 1, 1), None);
+    assert_eq!(tiles.layer_cells(Layer::Overlay).count(), 1);
+    assert_eq!(tiles.layer_cells(Layer::Ground).count(), 12);
+}
+
+#[test]
+fn painting_only_touches_the_active_layer() {
+    let tiles = layered_room();
+    let mut cell = tiles.cell(1, 1).unwrap();
+    let source = Rect::new(64.0, 0.0, 16.0, 16.0);
+
+    paint_cell(&mut cell, Layer::Overlay, source);
+    assert_eq!(cell.overlay, source);
+    assert_eq!(cell.decoration, Rect::new(32.0, 16.0, 16.0, 16.0));
+
+    paint_cell(&mut cell, Layer::Collision, source);
+    assert!(cell.solid);
+    assert_eq!(cell.tile.source(), Rect::new(16.0, 0.0, 16.0, 16.0));
+
+    erase_cell(&mut cell, Layer::Decoration);
+    assert_eq!(cell.source(Layer::Decoration), None);
+    assert!(cell.solid);
+
+    erase_cell(&mut cell, Layer::Collision);
+    assert!(!cell.solid);
+    assert_eq!(cell.tile.tile_type(), TileType::Floor(1));
+}
+
+#[test]
+fn erasing_ground_keeps_the_wall_underneath() {
+    let mut tiles = layered_room();
+    tiles.set_wall(2, 1, true);
+    let mut cell = tiles.cell(2, 1).unwrap();
+
+    erase_cell(&mut cell, Layer::Ground);
+    tiles.set_cell(2, 1, cell);
+
+    assert_eq!(tiles.layer_source(Layer::Ground, 2, 1), None);
+    assert!(tiles.is_wall(2, 1));
+    assert!(!NavGrid::from_tile_map(&tiles).is_walkable(2, 1));
+}
+
+#[test]
+fn resizing_keeps_every_layer() {
+    let grown = layered_room().resized(6, 5, Anchor::BottomRight);
+
+    assert_eq!(
+        grown.layer_source(Layer::Decoration, 3, 3),
+        Some(Rect::new(32.0, 16.0, 16.0, 16.0))
+    );
+    assert_eq!(
+        grown.layer_source(Layer::Overlay, 5, 4),
+        Some(Rect::new(48.0, 32.0, 16.0, 16.0))
+    );
+}
+
+#[test]
+fn layers_round_trip_through_json_and_msgpack() {
+    let dir = scratch_dir("tile_layers");
+    let level = LevelData::new("layered", layered_room());
+
+    for path in ["layered.json", "layered.msgpack"] {
+        save_to(&dir, &level, path).unwrap();
+        let loaded = load_from(&dir, path).unwrap();
+
+        for layer in Layer::ALL {
+            assert_eq!(
+                loaded.tiles.layer_cells(layer).collect::<Vec<_>>(),
+                level.tiles.layer_cells(layer).collect::<Vec<_>>(),
+                "{} layer differs in {}",
+                layer.name(),
+                path
+            );
+        }
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn version_three_levels_load_without_layers() {
+    let json = r#"{
+        "version": 3, "name": "plain", "width": 2, "height": 1, "tile_size": 32.0,
+        "atlas": "res/textures/tilemap_packed.png", "player_spawn": null,
+        "tiles": {"atlas_tile_size": 16.0, "atlas_columns": 12,
+                  "atlas": {"Raw": [0, 1]}, "collision": {"Raw": [3, 1]}}
+    }"#;
+
+    let level = Level::parse(json, "plain").unwrap().into_data();
+
+    assert_eq!(level.tiles.layer_cells(Layer::Ground).count(), 2);
+    assert_eq!(level.tiles.layer_cells(Layer::Decoration).count(), 0);
+    assert_eq!(level.tiles.layer_cells(Layer::Overlay).count(), 0);
+}
+
+#[test]
+fn layer_cells_outside_the_map_are_rejected() {
+    let json = r#"{
+        "version": 4, "name": "broken", "width": 1, "height": 1, "tile_size": 32.0,
+        "atlas": "", "player_spawn": null, "tiles": [],
+        "layers": [{"layer": "Overlay", "cells": [{"col": 3, "row": 0,
+                    "source": {"x": 0.0, "y": 0.0, "w": 16.0, "h": 16.0}}]}]
+    }"#;
+
+    assert!(matches!(
+        Level::parse(json, "broken"),
+        Err(LevelError::Validation(_))
+    ));
+}
+
+#[test]
+fn validation_checks_decoration_and_overlay_rects() {
+    let mut tiles = layered_room();
+    tiles.set_layer_source(Layer::Overlay, 2, 0, Rect::new(184.0, 0.0, 16.0, 16.0));
+
+    let diagnostics = validate_level(&tiles, ATLAS_SIZE);
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::InvalidAtlasRect {
+            layer: Layer::Overlay,
+            cell: (2, 0),
+            source: Rect::new(184.0, 0.0, 16.0, 16.0)
+        }]
+    );
+}
@@ -0,0 +1,55 @@
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+#[test]
+fn negative_positions_map_to_cells_outside_the_grid() {
+    let tiles = TileMap::new(4, 4);
+
+    assert_eq!(world_to_grid(vec2(-5.0, 10.0)), (-1, 0));
+    assert_eq!(calculate_tile_pos(-5.0, -40.0), vec2(-32.0, -64.0));
+    assert!(tiles.tile_at(vec2(-5.0, 10.0)).is_none());
+    assert_eq!(
+        tiles.tile_at(vec2(5.0, 10.0)).map(|tile| tile.pos()),
+        Some(Vec2::ZERO)
+    );
+}
+
+#[test]
+fn tile_positions_snap_down_to_their_cell() {
+    assert_eq!(calculate_tile_pos(63.9, 32.0), vec2(32.0, 32.0));
+    assert_eq!(grid_to_world(2, 3), vec2(64.0, 96.0));
+}
+
+#[test]
+fn cell_writes_keep_the_grid_position() {
+    let mut tiles = TileMap::new(4, 4);
+    let mut cell = tiles.cell(3, 3).unwrap();
+    cell.tile = Tile::new(vec2(5.0, 7.0), cell.tile.source(), TileType::Floor(1));
+
+    tiles.set_cell(1, 2, cell);
+    tiles.set_type(2, 2, TileType::Floor(1));
+
+    assert_eq!(tiles.get(1, 2).unwrap().pos(), grid_to_world(1, 2));
+    assert_eq!(tiles.get(1, 2).unwrap().tile_type(), TileType::Floor(1));
+    assert!(tiles
+        .cells()
+        .all(|(col, row, tile)| tile.pos() == grid_to_world(col, row)));
+}
+
+#[test]
+fn map_sizes_are_bounded_and_cell_counts_checked() {
+    assert_eq!(cell_count(3, 4), Some(12));
+    assert_eq!(cell_count(-3, 4), Some(0));
+    assert_eq!(cell_count(i32::MAX, 2), None);
+
+    let tiles = TileMap::new(i32::MAX, 2);
+    assert_eq!((tiles.width(), tiles.height()), (MAX_MAP_SIZE, 2));
+
+    let far = Tile::new(vec2(1.0e30, 1.0e12), Rect::default(), TileType::Floor(1));
+    let tiles = TileMap::from_tiles(vec![far]);
+    assert_eq!(
+        (tiles.width(), tiles.height()),
+        (MAX_MAP_SIZE, MAX_MAP_SIZE)
+    );
+}
@@ -0,0 +1,97 @@
+use dungeon_game::input::*;
+use dungeon_game::player::*;
+use dungeon_game::tile::*;
+use dungeon_game::timestep::*;
+
+use macroquad::prelude::*;
+
+fn open_floor() -> TileMap {
+    let mut tiles = TileMap::new(40, 10);
+    tiles.fill(TileType::Floor(1), empty_source());
+
+    tiles
+}
+
+fn run_player(frame_time: f32, duration: f32) -> (Vec2, u32) {
+    let tiles = open_floor();
+    let mut player = Player::new(64.0, 160.0, 32.0, 32.0, 100.0);
+    let mut timestep = FixedTimestep::new(FIXED_DT);
+    let input = InputSnapshot {
+        movement: vec2(1.0, 0.0),
+        aim: vec2(1.0, 0.0),
+        ..Default::default()
+    };
+
+    let mut elapsed = 0.0;
+    let mut steps = 0;
+    while elapsed + frame_time <= duration + 1e-4 {
+        for _ in 0..timestep.advance(frame_time) {
+            player.update(&input, &tiles, &mut [], timestep.step());
+            steps += 1;
+        }
+        elapsed += frame_time;
+    }
+
+    (player.pos(), steps)
+}
+
+#[test]
+fn advance_runs_whole_steps_and_keeps_remainder() {
+    let mut timestep = FixedTimestep::new(0.1);
+
+    assert_eq!(timestep.advance(0.25), 2);
+    assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+    assert_eq!(timestep.advance(0.06), 1);
+    assert!(timestep.accumulator() < 0.1);
+
+    timestep.reset();
+    assert_eq!(timestep.alpha(), 0.0);
+}
+
+#[test]
+fn long_frames_are_clamped() {
+    let mut timestep = FixedTimestep::new(FIXED_DT);
+
+    let steps = timestep.advance(5.0);
+
+    assert_eq!(steps, (MAX_FRAME_TIME / FIXED_DT).round() as u32);
+}
+
+#[test]
+#[should_panic(expected = "fixed timestep must be positive")]
+fn zero_step_is_rejected() {
+    FixedTimestep::new(0.0);
+}
+
+#[test]
+#[should_panic(expected = "fixed timestep must be positive")]
+fn negative_step_is_rejected() {
+    FixedTimestep::new(-FIXED_DT);
+}
+
+#[test]
+fn player_movement_is_frame_rate_independent() {
+    let (slow_pos, slow_steps) = run_player(1.0 / 20.0, 1.0);
+    let (fast_pos, fast_steps) = run_player(1.0 / 120.0, 1.0);
+
+    assert_eq!(slow_steps, fast_steps);
+    assert_eq!(slow_pos, fast_pos);
+    assert!((slow_pos.x - (64.0 + 100.0 * FIXED_DT * slow_steps as f32)).abs() < 0.01);
+}
+
+#[test]
+fn render_position_interpolates_between_steps() {
+    let tiles = open_floor();
+    let mut player = Player::new(64.0, 160.0, 32.0, 32.0, 100.0);
+    let input = InputSnapshot {
+        movement: vec2(1.0, 0.0),
+        ..Default::default()
+    };
+
+    player.update(&input, &tiles, &mut [], FIXED_DT);
+
+    let step = 100.0 * FIXED_DT;
+    assert_eq!(player.render_pos(0.0), vec2(64.0, 160.0));
+    assert!((player.render_pos(0.5).x - (64.0 + step * 0.5)).abs() < 1e-4);
+    assert_eq!(player.render_pos(1.0), player.pos());
+}
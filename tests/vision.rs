@@ -0,0 +1,45 @@
+use dungeon_game::enemy::navigation::*;
+use dungeon_game::enemy::vision::*;
+use dungeon_game::tile::*;
+
+use macroquad::prelude::*;
+
+fn corridor() -> NavGrid {
+    let mut tiles = TileMap::new(10, 3);
+    tiles.fill(TileType::Floor(1), empty_source());
+    tiles.set_wall(5, 1, true);
+
+    NavGrid::from_tile_map(&tiles)
+}
+
+#[test]
+fn targets_inside_the_cone_are_seen() {
+    let vision = Vision::new(200.0, std::f32::consts::FRAC_PI_2);
+    let origin = vec2(48.0, 16.0);
+
+    assert!(vision.in_cone(origin, vec2(1.0, 0.0), vec2(150.0, 16.0)));
+    assert!(vision.in_cone(origin, vec2(1.0, 0.0), vec2(148.0, 80.0)));
+    assert!(vision.can_see(origin, vec2(1.0, 0.0), vec2(150.0, 16.0), &corridor()));
+}
+
+#[test]
+fn targets_outside_the_cone_or_range_are_missed() {
+    let vision = Vision::new(200.0, std::f32::consts::FRAC_PI_2);
+    let origin = vec2(48.0, 16.0);
+
+    assert!(!vision.in_cone(origin, vec2(1.0, 0.0), vec2(0.0, 16.0)));
+    assert!(!vision.in_cone(origin, vec2(1.0, 0.0), vec2(60.0, 80.0)));
+    assert!(!vision.in_cone(origin, vec2(1.0, 0.0), vec2(300.0, 16.0)));
+}
+
+#[test]
+fn walls_block_line_of_sight_inside_the_cone() {
+    let vision = Vision::new(400.0, std::f32::consts::PI);
+    let nav_grid = corridor();
+    let origin = vec2(48.0, 48.0);
+    let target = vec2(272.0, 48.0);
+
+    assert!(vision.in_cone(origin, vec2(1.0, 0.0), target));
+    assert!(!vision.can_see(origin, vec2(1.0, 0.0), target, &nav_grid));
+    assert!(vision.can_see(origin, vec2(1.0, 0.0), vec2(144.0, 48.0), &nav_grid));
+}
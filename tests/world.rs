@@ -0,0 +1,135 @@
+mod common;
+
+use common::*;
+use dungeon_game::enemy::ai::*;
+use dungeon_game::enemy::patrol::*;
+use dungeon_game::enemy::spawn::*;
+use dungeon_game::enemy::*;
+use dungeon_game::input::*;
+use dungeon_game::serialization::*;
+use dungeon_game::tile::*;
+use dungeon_game::timestep::*;
+use dungeon_game::world::*;
+
+use macroquad::prelude::*;
+
+fn idle_input(aim: Vec2) -> InputSnapshot {
+    InputSnapshot {
+        aim,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn world_spawns_player_on_spawn_tile() {
+    let world = World::new(spawn_room(8, 8), 0);
+
+    assert_eq!(world.player().pos(), grid_to_world(1, 1));
+    assert!(world.enemies().is_empty());
+}
+
+#[test]
+fn player_walks_through_the_world() {
+    let mut world = World::new(spawn_room(12, 4), 0);
+    let input = InputSnapshot {
+        movement: vec2(1.0, 0.0),
+        aim: vec2(1.0, 0.0),
+        ..Default::default()
+    };
+
+    for _ in 0..60 {
+        world.step(&input, FIXED_DT);
+    }
+
+    let travelled = world.player().pos().x - grid_to_world(1, 1).x;
+    assert!((travelled - 100.0).abs() < 0.5);
+}
+
+#[test]
+fn enemy_chases_and_hurts_idle_player() {
+    let mut world = World::new(spawn_room(10, 10), 3);
+    world.spawn_enemies(
+        &[EnemySpawn::new(grid_to_world(4, 1), EnemyKind::Sword, 1)],
+        &[],
+        false,
+    );
+    world.enemies_mut()[0].set_facing(vec2(-1.0, 0.0));
+
+    let input = idle_input(vec2(1.0, 0.0));
+    let mut states = Vec::new();
+    for _ in 0..600 {
+        world.step(&input, FIXED_DT);
+        states.push(world.enemies()[0].state());
+
+        if world.player().health() < 3 {
+            break;
+        }
+    }
+
+    assert!(states.contains(&AiState::Chase));
+    assert!(states.contains(&AiState::Attack));
+    assert!(world.player().health() < 3);
+}
+
+#[test]
+fn player_attacks_kill_an_enemy() {
+    let mut world = World::new(spawn_room(10, 10), 3);
+    world.spawn_enemies(
+        &[EnemySpawn::new(grid_to_world(2, 1), EnemyKind::Sword, 1)],
+        &[],
+        false,
+    );
+    world.player_mut().set_health(100);
+
+    for frame in 0..1200 {
+        let player_pos = world.player().pos();
+        let input = InputSnapshot {
+            aim: world
+                .enemies()
+                .first()
+                .map(|enemy| (enemy.pos() - player_pos).normalize_or_zero())
+                .unwrap_or_default(),
+            attack: frame % 30 == 0,
+            ..Default::default()
+        };
+        world.step(&input, FIXED_DT);
+
+        if world.enemies().is_empty() {
+            break;
+        }
+    }
+
+    assert!(world.enemies().is_empty());
+}
+
+#[test]
+fn random_fallback_only_runs_without_placed_spawns() {
+    let tiles = load("level_0").unwrap().tiles;
+
+    let mut world = World::new(tiles.clone(), 11);
+    world.spawn_enemies(&[], &[], false);
+    assert!(world.enemies().is_empty());
+
+    let mut world = World::new(tiles, 11);
+    world.spawn_enemies(&[], &[], true);
+    assert_eq!(world.enemies().len(), RANDOM_ENEMY_COUNT);
+}
+
+#[test]
+fn every_patrol_route_gets_its_own_enemy_at_the_first_waypoint() {
+    let mut world = World::new(spawn_room(10, 10), 5);
+    let spawns = vec![EnemySpawn::new(grid_to_world(8, 8), EnemyKind::Sword, 1)];
+    let routes = vec![
+        PatrolRoute::new(vec![grid_to_world(2, 5), grid_to_world(6, 5)]),
+        PatrolRoute::new(vec![grid_to_world(4, 2), grid_to_world(4, 7)]),
+    ];
+
+    world.spawn_enemies(&spawns, &routes, true);
+
+    assert_eq!(world.enemies().len(), 3);
+    assert!(world.enemies()[0].patrol().is_none());
+    for (enemy, route) in world.enemies()[1..].iter().zip(routes.iter()) {
+        assert_eq!(enemy.pos(), route.waypoint(0).unwrap());
+        assert_eq!(enemy.patrol(), Some(route));
+    }
+}